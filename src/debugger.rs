@@ -0,0 +1,181 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use memflow::prelude::v1::*;
+
+/// Number of emulated hardware breakpoint registers (`Dr0` - `Dr3`).
+const MAX_WATCHPOINTS: usize = 4;
+/// Maximum number of pending events, the oldest events are dropped when ReClass.NET falls behind.
+const MAX_EVENTS: usize = 1024;
+
+/// A detected write to a watched address.
+#[derive(Clone, Copy, Debug)]
+pub struct WatchEvent {
+    /// The emulated breakpoint register, `Dr0` = 1 to `Dr3` = 4.
+    pub register: i32,
+}
+
+/// Events raised by the polling thread which are picked up by `AwaitDebugEvent`.
+#[derive(Default)]
+pub struct EventQueue {
+    events: Mutex<VecDeque<WatchEvent>>,
+    available: Condvar,
+}
+
+impl EventQueue {
+    fn push(&self, event: WatchEvent) {
+        if let Ok(mut events) = self.events.lock() {
+            if events.len() >= MAX_EVENTS {
+                events.pop_front();
+            }
+            events.push_back(event);
+            self.available.notify_all();
+        }
+    }
+
+    /// Waits up to `timeout` for the next event.
+    pub fn wait(&self, timeout: Duration) -> Option<WatchEvent> {
+        let events = self.events.lock().ok()?;
+        let (mut events, _) = self
+            .available
+            .wait_timeout_while(events, timeout, |events| events.is_empty())
+            .ok()?;
+        events.pop_front()
+    }
+}
+
+struct Watchpoint {
+    address: Address,
+    size: usize,
+    value: [u8; 8],
+}
+
+type Watchpoints = [Option<Watchpoint>; MAX_WATCHPOINTS];
+
+/// Emulates hardware write breakpoints in a process by polling the watched addresses.
+///
+/// memflow can neither suspend nor single-step the target, so this only detects writes
+/// which change the watched value and were not reverted between two polls.
+/// The writing thread, instruction and registers are unknown.
+pub struct WriteWatch {
+    process_id: u32,
+    watchpoints: Arc<Mutex<Watchpoints>>,
+    events: Arc<EventQueue>,
+    running: Arc<AtomicBool>,
+}
+
+impl WriteWatch {
+    pub fn new(process_id: u32) -> Self {
+        Self {
+            process_id,
+            watchpoints: Default::default(),
+            events: Arc::new(EventQueue::default()),
+            running: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    pub fn process_id(&self) -> u32 {
+        self.process_id
+    }
+
+    pub fn events(&self) -> Arc<EventQueue> {
+        self.events.clone()
+    }
+
+    /// Returns the handle the polling thread uses to read the watched addresses.
+    ///
+    /// The poller stops running once the watch is dropped.
+    pub fn poller(&self) -> WatchPoller {
+        WatchPoller {
+            watchpoints: self.watchpoints.clone(),
+            events: self.events.clone(),
+            running: self.running.clone(),
+        }
+    }
+
+    /// Watches `size` bytes at `address` in the given breakpoint register (`Dr0` = 1 to `Dr3` = 4).
+    ///
+    /// Fails if the register or size is invalid or the address cannot be read.
+    pub fn set<F: FnMut(Address, &mut [u8]) -> bool>(
+        &self,
+        register: i32,
+        address: Address,
+        size: usize,
+        mut read: F,
+    ) -> bool {
+        if ![1, 2, 4, 8].contains(&size) {
+            return false;
+        }
+
+        let mut value = [0u8; 8];
+        if !read(address, &mut value[..size]) {
+            return false;
+        }
+
+        self.with_slot(register, |slot| {
+            *slot = Some(Watchpoint {
+                address,
+                size,
+                value,
+            })
+        })
+    }
+
+    pub fn clear(&self, register: i32) -> bool {
+        self.with_slot(register, |slot| *slot = None)
+    }
+
+    /// Calls `f` with the watchpoint of a breakpoint register, returns `false` if the register is invalid.
+    fn with_slot<F: FnOnce(&mut Option<Watchpoint>)>(&self, register: i32, f: F) -> bool {
+        if register < 1 {
+            return false;
+        }
+        match self.watchpoints.lock() {
+            Ok(mut watchpoints) => watchpoints.get_mut(register as usize - 1).map(f).is_some(),
+            Err(_) => false,
+        }
+    }
+}
+
+impl Drop for WriteWatch {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+/// The polling thread side of a `WriteWatch`.
+pub struct WatchPoller {
+    watchpoints: Arc<Mutex<Watchpoints>>,
+    events: Arc<EventQueue>,
+    running: Arc<AtomicBool>,
+}
+
+impl WatchPoller {
+    /// Returns `false` once the watch has been dropped and polling should stop.
+    pub fn running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    /// Reads all watched values and raises an event for each value that changed since the last poll.
+    pub fn poll<F: FnMut(Address, &mut [u8]) -> bool>(&self, mut read: F) {
+        let mut watchpoints = match self.watchpoints.lock() {
+            Ok(watchpoints) => watchpoints,
+            Err(_) => return,
+        };
+        for (idx, watchpoint) in watchpoints.iter_mut().enumerate() {
+            if let Some(watchpoint) = watchpoint {
+                let mut value = [0u8; 8];
+                if read(watchpoint.address, &mut value[..watchpoint.size])
+                    && value != watchpoint.value
+                {
+                    watchpoint.value = value;
+                    self.events.push(WatchEvent {
+                        register: idx as i32 + 1,
+                    });
+                }
+            }
+        }
+    }
+}
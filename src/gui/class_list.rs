@@ -0,0 +1,84 @@
+use super::{results, support};
+
+use imgui::*;
+use memflow::prelude::v1::*;
+
+/// Displays the window listing all classes with run-time type information in a module.
+///
+/// The `list` function is invoked with the module name and returns all vtables with their class names.
+/// This function blocks until the user closes the window.
+pub fn show<F: FnMut(&str) -> Result<Vec<(Address, String)>>>(mut list: F) {
+    let mut module = ImString::with_capacity(256);
+    let mut filter = ImString::with_capacity(256);
+    let mut status = String::new();
+    let mut classes = Vec::new();
+    let mut filtered = Vec::new();
+
+    support::show_window("memflow - Class List", 600.0, 460.0, |run, ui| {
+        Window::new(im_str!("Class List"))
+            .position([10.0, 10.0], Condition::Always)
+            .size([575.0, 1000.0], Condition::Always)
+            .title_bar(false)
+            .resizable(false)
+            .movable(false)
+            .scroll_bar(false)
+            .save_settings(false)
+            .focus_on_appearing(false)
+            .build(ui, || {
+                ui.text(im_str!("Module"));
+                ui.separator();
+
+                ui.input_text(im_str!("Module"), &mut module)
+                    .resize_buffer(true)
+                    .build();
+
+                let mut update = ui
+                    .input_text(im_str!("Filter"), &mut filter)
+                    .resize_buffer(true)
+                    .build();
+
+                if ui.button(im_str!("List"), [64.0, 26.0]) {
+                    match list(module.to_str().trim()) {
+                        Ok(results) => {
+                            status = format!("{} classes", results.len());
+                            classes = results;
+                        }
+                        Err(err) => {
+                            status = format!("listing failed: {}", err.as_str());
+                            classes.clear();
+                        }
+                    }
+                    update = true;
+                }
+
+                if update {
+                    let filter = filter.to_str().to_lowercase();
+                    filtered = classes
+                        .iter()
+                        .filter(|(_, name)| name.to_lowercase().contains(&filter))
+                        .cloned()
+                        .collect();
+                }
+
+                ui.same_line(64.0 + 16.0);
+                ui.text(&status);
+
+                ui.dummy([0.0, 8.0]);
+
+                ui.text(im_str!("Classes"));
+                ui.separator();
+
+                results::address_list(ui, im_str!("##classes"), &filtered, 250.0);
+
+                if ui.button(im_str!("Copy All"), [64.0, 26.0]) {
+                    results::copy_addresses(ui, &filtered);
+                }
+
+                ui.same_line(64.0 + 16.0);
+
+                if ui.button(im_str!("Close"), [64.0, 26.0]) {
+                    *run = false;
+                }
+            });
+    })
+}
@@ -0,0 +1,349 @@
+use imgui::*;
+use memflow::plugins::args::split_str_args;
+use memflow::prelude::v1::*;
+
+/// Key of the unnamed argument which is written first without its key.
+const DEFAULT_KEY: &str = "default";
+/// Quotes which memflow accepts around values.
+const QUOTES: [char; 3] = ['"', '\'', '`'];
+
+const ERROR_COLOR: [f32; 4] = [1.0, 0.4, 0.4, 1.0];
+const WARNING_COLOR: [f32; 4] = [1.0, 0.8, 0.3, 1.0];
+
+/// An argument a connector accepts as described in its help text.
+#[derive(Clone, Debug)]
+pub struct ArgHelp {
+    pub name: String,
+    pub description: String,
+    pub required: bool,
+}
+
+/// Parses the argument descriptions of a connector help text.
+///
+/// Connectors print their `ArgsValidator` which lists one `name: description` per line,
+/// required arguments are suffixed with ` (required)`. Other lines are ignored.
+pub fn parse_help(help: &str) -> Vec<ArgHelp> {
+    help.lines()
+        .filter_map(|line| {
+            let (name, description) = line.trim().split_once(": ")?;
+            if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                return None;
+            }
+            let (description, required) = match description.strip_suffix(" (required)") {
+                Some(description) => (description, true),
+                None => (description, false),
+            };
+            Some(ArgHelp {
+                name: name.to_string(),
+                description: description.to_string(),
+                required,
+            })
+        })
+        .collect()
+}
+
+/// Splits an argument string into its key-value pairs in the order they were entered.
+///
+/// A leading value without a key is returned with the `default` key.
+fn parse_args(args: &str) -> Vec<(String, String)> {
+    split_str_args(args, ',')
+        .enumerate()
+        .filter(|(_, arg)| !arg.is_empty())
+        .map(|(idx, arg)| {
+            let kv = split_str_args(arg, '=').collect::<Vec<_>>();
+            match kv.as_slice() {
+                [key, value] => (key.to_string(), value.to_string()),
+                _ if idx == 0 => (DEFAULT_KEY.to_string(), arg.to_string()),
+                _ => (arg.to_string(), String::new()),
+            }
+        })
+        .collect()
+}
+
+/// Joins key-value pairs into an argument string which can be parsed by memflow.
+fn format_args(rows: &[(String, String)]) -> String {
+    // quotes inside of a value start a quoted section when parsed, so they have to be quoted as well
+    let quote = |value: &str| {
+        if !value.contains(&[',', '='][..]) && !value.contains(&QUOTES[..]) {
+            return value.to_string();
+        }
+        match QUOTES.iter().find(|quote| !value.contains(**quote)) {
+            Some(quote) => format!("{}{}{}", quote, value, quote),
+            None => value.to_string(),
+        }
+    };
+
+    let default = rows.iter().find(|(key, _)| key == DEFAULT_KEY);
+    default
+        .map(|(_, value)| quote(value))
+        .into_iter()
+        .chain(
+            rows.iter()
+                .filter(|(key, _)| !key.is_empty() && key != DEFAULT_KEY)
+                .map(|(key, value)| format!("{}={}", key, quote(value))),
+        )
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// A structured editor for the argument string of a connector.
+///
+/// The arguments are edited as key-value rows which are validated against the arguments
+/// listed in the help text of the selected connector. The raw argument string can still
+/// be edited directly in the advanced mode.
+pub struct ArgsForm {
+    rows: Vec<(ImString, ImString)>,
+    raw: ImString,
+    advanced: bool,
+    connector: String,
+    help: String,
+    known: Vec<ArgHelp>,
+    add_idx: usize,
+}
+
+impl ArgsForm {
+    pub fn new(args: &str) -> Self {
+        let mut form = Self {
+            rows: Vec::new(),
+            raw: ImString::new(args),
+            advanced: false,
+            connector: String::new(),
+            help: String::new(),
+            known: Vec::new(),
+            add_idx: 0,
+        };
+        form.rows_from_raw();
+        form
+    }
+
+    /// Loads the help text of a connector if the selection changed.
+    pub fn set_connector(&mut self, inventory: &Inventory, connector: &str) {
+        if self.connector == connector {
+            return;
+        }
+        self.connector = connector.to_string();
+        self.help = inventory.connector_help(connector).unwrap_or_else(|err| {
+            log::debug!("unable to get help of connector {}: {}", connector, err);
+            String::new()
+        });
+        self.known = parse_help(&self.help);
+        self.add_idx = 0;
+    }
+
+    /// Returns the argument string.
+    pub fn args(&self) -> String {
+        if self.advanced {
+            self.raw.to_str().to_owned()
+        } else {
+            format_args(&self.entries())
+        }
+    }
+
+    /// Returns the problems which prevent the arguments from being used.
+    pub fn errors(&self) -> Vec<String> {
+        if self.advanced {
+            return Vec::new();
+        }
+
+        let entries = self.entries();
+        let mut errors = Vec::new();
+        for (idx, (key, value)) in entries.iter().enumerate() {
+            if key.is_empty() {
+                errors.push(format!("argument {} has no name", idx + 1));
+            } else if key.contains(&[',', '='][..]) || key.contains(&QUOTES[..]) {
+                errors.push(format!("argument name `{}` is invalid", key));
+            } else if entries[..idx].iter().any(|(other, _)| other == key) {
+                errors.push(format!("argument `{}` is set more than once", key));
+            }
+            if QUOTES.iter().all(|quote| value.contains(*quote)) {
+                errors.push(format!("value of `{}` can not contain all quotes", key));
+            }
+            if key == DEFAULT_KEY && value.contains('=') {
+                errors.push(format!("value of `{}` can not contain `=`", key));
+            }
+        }
+        errors
+    }
+
+    /// Returns hints about arguments which are likely wrong but do not prevent loading.
+    pub fn warnings(&self) -> Vec<String> {
+        if self.advanced || self.known.is_empty() {
+            return Vec::new();
+        }
+
+        let entries = self.entries();
+        let unknown = entries
+            .iter()
+            .filter(|(key, _)| {
+                !key.is_empty()
+                    && key != DEFAULT_KEY
+                    && !self.known.iter().any(|arg| &arg.name == key)
+            })
+            .map(|(key, _)| format!("`{}` is not a known argument of {}", key, self.connector));
+        let missing = self
+            .known
+            .iter()
+            .filter(|arg| arg.required && !entries.iter().any(|(key, _)| key == &arg.name))
+            .map(|arg| format!("`{}` is required by {}", arg.name, self.connector));
+        unknown.chain(missing).collect()
+    }
+
+    /// Renders the argument rows, the help text and the advanced raw input.
+    pub fn build(&mut self, ui: &Ui) {
+        if ui.checkbox(im_str!("Raw Args"), &mut self.advanced) {
+            // carry the arguments over to the other mode
+            if self.advanced {
+                self.raw = ImString::new(format_args(&self.entries()));
+            } else {
+                self.rows_from_raw();
+            }
+        }
+
+        if self.advanced {
+            ui.input_text(im_str!("Args"), &mut self.raw)
+                .resize_buffer(true)
+                .build();
+        } else {
+            self.build_rows(ui);
+        }
+
+        for error in self.errors().iter() {
+            ui.text_colored(ERROR_COLOR, error);
+        }
+        for warning in self.warnings().iter() {
+            ui.text_colored(WARNING_COLOR, warning);
+        }
+
+        if !self.help.is_empty() && CollapsingHeader::new(im_str!("Connector Help")).build(ui) {
+            ui.text_wrapped(&ImString::new(&self.help));
+        }
+    }
+
+    fn build_rows(&mut self, ui: &Ui) {
+        let mut remove = None;
+        for (idx, (key, value)) in self.rows.iter_mut().enumerate() {
+            let id = ui.push_id(idx as i32);
+            ui.set_next_item_width(100.0);
+            ui.input_text(im_str!("##key"), key)
+                .resize_buffer(true)
+                .build();
+            ui.same_line(0.0);
+            ui.set_next_item_width(180.0);
+            ui.input_text(im_str!("##value"), value)
+                .resize_buffer(true)
+                .build();
+            ui.same_line(0.0);
+            if ui.small_button(im_str!("x")) {
+                remove = Some(idx);
+            }
+            id.pop(ui);
+        }
+        if let Some(idx) = remove {
+            self.rows.remove(idx);
+        }
+
+        // offer the arguments listed in the help text, or an empty row if there are none
+        let names = self
+            .known
+            .iter()
+            .map(|arg| ImString::new(&arg.name))
+            .collect::<Vec<_>>();
+        if !names.is_empty() {
+            let names_ref: Vec<&ImStr> = names.iter().map(|name| name.as_ref()).collect();
+            ui.set_next_item_width(100.0);
+            ComboBox::new(im_str!("##known")).build_simple_string(
+                ui,
+                &mut self.add_idx,
+                &names_ref[..],
+            );
+            if ui.is_item_hovered() {
+                if let Some(arg) = self.known.get(self.add_idx) {
+                    ui.tooltip_text(&arg.description);
+                }
+            }
+            ui.same_line(0.0);
+        }
+        if ui.small_button(im_str!("Add Argument")) {
+            let key = self
+                .known
+                .get(self.add_idx)
+                .map(|arg| arg.name.as_str())
+                .unwrap_or_default();
+            self.rows.push((ImString::new(key), ImString::default()));
+        }
+    }
+
+    /// Replaces the rows with the arguments of the raw argument string.
+    fn rows_from_raw(&mut self) {
+        self.rows = parse_args(self.raw.to_str())
+            .into_iter()
+            .map(|(key, value)| (ImString::new(key), ImString::new(value)))
+            .collect();
+    }
+
+    /// Returns the non-empty rows as trimmed key-value pairs.
+    fn entries(&self) -> Vec<(String, String)> {
+        self.rows
+            .iter()
+            .map(|(key, value)| (key.to_str().trim(), value.to_str().trim()))
+            .filter(|(key, value)| !key.is_empty() || !value.is_empty())
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(rows: &[(&str, &str)]) -> Vec<(String, String)> {
+        rows.iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn help_arguments() {
+        let help = "The qemu connector implements a memflow plugin interface\n\
+                    \n\
+                    default: name of the qemu process\n\
+                    map_base: override of the VM memory base (required)\n\
+                    not an argument: line\n";
+        let args = parse_help(help);
+        assert_eq!(args.len(), 2);
+        assert_eq!(args[0].name, "default");
+        assert_eq!(args[0].description, "name of the qemu process");
+        assert!(!args[0].required);
+        assert_eq!(args[1].name, "map_base");
+        assert_eq!(args[1].description, "override of the VM memory base");
+        assert!(args[1].required);
+    }
+
+    #[test]
+    fn args_default_value() {
+        assert_eq!(
+            parse_args("win10,map_base=0x1000,,flag"),
+            rows(&[("default", "win10"), ("map_base", "0x1000"), ("flag", "")])
+        );
+    }
+
+    #[test]
+    fn args_round_trip() {
+        let args = rows(&[
+            ("default", "vm,1"),
+            ("path", "C:\\dumps\\mem.raw"),
+            ("a", "x=y"),
+            ("b", "it's"),
+            ("c", "say \"hi\", it's"),
+            ("d", "`cmd`, 'b'"),
+        ]);
+        let formatted = format_args(&args);
+        assert_eq!(parse_args(&formatted), args);
+
+        // the arguments have to be read the same way by memflow
+        let parsed: Args = formatted.parse().unwrap();
+        for (key, value) in args.iter() {
+            assert_eq!(parsed.get(key), Some(value.as_str()));
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use super::{results, support};
+use crate::logging;
+
+use std::time::{Duration, Instant};
+
+use imgui::*;
+
+/// Interval in which new log entries are picked up.
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Displays the most recent log entries of the plugin, memflow and the connector.
+///
+/// Clicking an entry copies it to the clipboard, `Copy All` copies all entries matching the filter.
+/// This function blocks until the user closes the window.
+pub fn show() {
+    let mut filter = ImString::with_capacity(256);
+    let mut follow = true;
+    let mut entries = logging::recent();
+    let mut filtered = Vec::new();
+    let mut last_refresh: Option<Instant> = None;
+
+    support::show_window("memflow - Log", 800.0, 460.0, |run, ui| {
+        Window::new(im_str!("Log"))
+            .position([10.0, 10.0], Condition::Always)
+            .size([775.0, 1000.0], Condition::Always)
+            .title_bar(false)
+            .resizable(false)
+            .movable(false)
+            .scroll_bar(false)
+            .save_settings(false)
+            .focus_on_appearing(false)
+            .build(ui, || {
+                let mut update = ui
+                    .input_text(im_str!("Filter"), &mut filter)
+                    .resize_buffer(true)
+                    .build();
+                ui.same_line(0.0);
+                ui.checkbox(im_str!("Follow"), &mut follow);
+
+                let refresh = last_refresh
+                    .map(|last| last.elapsed() >= REFRESH_INTERVAL)
+                    .unwrap_or(true);
+                if follow && refresh {
+                    entries = logging::recent();
+                    last_refresh = Some(Instant::now());
+                    update = true;
+                }
+
+                if update {
+                    let filter = filter.to_str().to_lowercase();
+                    filtered = entries
+                        .iter()
+                        .filter(|entry| entry.to_lowercase().contains(&filter))
+                        .cloned()
+                        .collect();
+                }
+
+                ui.text(format!("{} of {} entries", filtered.len(), entries.len()));
+                ui.separator();
+
+                results::text_list(ui, im_str!("##entries"), &filtered, 350.0);
+
+                if ui.button(im_str!("Copy All"), [64.0, 26.0]) {
+                    ui.set_clipboard_text(&ImString::new(filtered.join("\n")));
+                }
+
+                ui.same_line(64.0 + 16.0);
+
+                if ui.button(im_str!("Clear"), [64.0, 26.0]) {
+                    logging::clear_recent();
+                    entries.clear();
+                    filtered.clear();
+                }
+
+                ui.same_line(2.0 * (64.0 + 16.0));
+
+                if ui.button(im_str!("Close"), [64.0, 26.0]) {
+                    *run = false;
+                }
+            });
+    });
+}
@@ -4,3 +4,14 @@ pub mod settings;
 pub use settings::{Config, Settings};
 
 pub mod alert;
+
+pub mod class_list;
+mod connector_args;
+pub mod log_viewer;
+pub mod pattern_scan;
+pub mod pointer_scan;
+mod results;
+pub mod snapshot_diff;
+pub mod status;
+pub mod string_search;
+pub mod value_scan;
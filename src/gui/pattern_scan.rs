@@ -0,0 +1,74 @@
+use super::{results, support};
+
+use imgui::*;
+use memflow::prelude::v1::*;
+
+/// Displays the pattern scanner window.
+///
+/// The `scan` function is invoked with the pattern, the module name (which might be empty)
+/// and the executable-only option whenever the user starts a scan.
+/// This function blocks until the user closes the window.
+pub fn show<F: FnMut(&str, &str, bool) -> Result<Vec<(Address, String)>>>(mut scan: F) {
+    let mut pattern = ImString::with_capacity(256);
+    let mut module = ImString::with_capacity(256);
+    let mut executable_only = true;
+    let mut status = String::new();
+    let mut scan_results = Vec::new();
+
+    support::show_window("memflow - Pattern Scanner", 600.0, 460.0, |run, ui| {
+        Window::new(im_str!("Pattern Scanner"))
+            .position([10.0, 10.0], Condition::Always)
+            .size([575.0, 1000.0], Condition::Always)
+            .title_bar(false)
+            .resizable(false)
+            .movable(false)
+            .scroll_bar(false)
+            .save_settings(false)
+            .focus_on_appearing(false)
+            .build(ui, || {
+                ui.text(im_str!("Pattern"));
+                ui.separator();
+
+                ui.input_text(im_str!("Pattern"), &mut pattern)
+                    .resize_buffer(true)
+                    .build();
+                ui.input_text(im_str!("Module"), &mut module)
+                    .resize_buffer(true)
+                    .build();
+                ui.checkbox(im_str!("Executable Only"), &mut executable_only);
+
+                if ui.button(im_str!("Scan"), [64.0, 26.0]) {
+                    match scan(pattern.to_str(), module.to_str().trim(), executable_only) {
+                        Ok(results) => {
+                            status = format!("{} results", results.len());
+                            scan_results = results;
+                        }
+                        Err(err) => {
+                            status = format!("scan failed: {}", err.as_str());
+                            scan_results.clear();
+                        }
+                    }
+                }
+
+                ui.same_line(64.0 + 16.0);
+                ui.text(&status);
+
+                ui.dummy([0.0, 8.0]);
+
+                ui.text(im_str!("Results"));
+                ui.separator();
+
+                results::address_list(ui, im_str!("##results"), &scan_results, 250.0);
+
+                if ui.button(im_str!("Copy All"), [64.0, 26.0]) {
+                    results::copy_addresses(ui, &scan_results);
+                }
+
+                ui.same_line(64.0 + 16.0);
+
+                if ui.button(im_str!("Close"), [64.0, 26.0]) {
+                    *run = false;
+                }
+            });
+    })
+}
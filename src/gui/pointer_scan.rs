@@ -0,0 +1,146 @@
+use super::{results, support};
+use crate::scan::pointer::{self, PointerChain, PointerScanOptions};
+
+use std::path::Path;
+
+use imgui::*;
+use memflow::prelude::v1::*;
+
+/// Displays the pointer scanner window.
+///
+/// The `scan` function is invoked with the target address and the scan options whenever the user starts a new scan.
+/// The `rescan` function is invoked with the new target address and the current results
+/// and returns the chains that still resolve to the target.
+/// Results can be saved to and loaded from a file so they can be rescanned after the target restarted.
+/// This function blocks until the user closes the window.
+pub fn show<S, R>(max_results: usize, mut scan: S, mut rescan: R)
+where
+    S: FnMut(Address, PointerScanOptions) -> Result<Vec<PointerChain>>,
+    R: FnMut(Address, &[PointerChain]) -> Result<Vec<PointerChain>>,
+{
+    let mut address = ImString::with_capacity(32);
+    let mut max_depth = 4;
+    let mut max_offset = ImString::new("800");
+    let mut path = ImString::with_capacity(256);
+    let mut status = String::new();
+    let mut chains = Vec::new();
+    let mut lines = Vec::new();
+
+    support::show_window("memflow - Pointer Scanner", 600.0, 520.0, |run, ui| {
+        Window::new(im_str!("Pointer Scanner"))
+            .position([10.0, 10.0], Condition::Always)
+            .size([575.0, 1000.0], Condition::Always)
+            .title_bar(false)
+            .resizable(false)
+            .movable(false)
+            .scroll_bar(false)
+            .save_settings(false)
+            .focus_on_appearing(false)
+            .build(ui, || {
+                ui.text(im_str!("Target"));
+                ui.separator();
+
+                ui.input_text(im_str!("Address"), &mut address)
+                    .resize_buffer(true)
+                    .build();
+                ui.input_int(im_str!("Max Depth"), &mut max_depth).build();
+                ui.input_text(im_str!("Max Offset"), &mut max_offset)
+                    .resize_buffer(true)
+                    .build();
+
+                let result = if ui.button(im_str!("Scan"), [64.0, 26.0]) {
+                    match (
+                        results::parse_hex(address.to_str()),
+                        results::parse_hex(max_offset.to_str()),
+                    ) {
+                        (Some(target), Some(max_offset)) => Some(scan(
+                            target.into(),
+                            PointerScanOptions {
+                                max_depth: max_depth.max(1) as usize,
+                                max_offset,
+                                max_results,
+                            },
+                        )),
+                        _ => {
+                            status = "invalid address or offset".to_string();
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
+
+                ui.same_line(64.0 + 16.0);
+
+                let result = if ui.button(im_str!("Rescan"), [64.0, 26.0]) {
+                    match results::parse_hex(address.to_str()) {
+                        Some(target) => Some(rescan(target.into(), &chains)),
+                        None => {
+                            status = "invalid address".to_string();
+                            None
+                        }
+                    }
+                } else {
+                    result
+                };
+
+                match result {
+                    Some(Ok(results)) => {
+                        status = format!("{} results", results.len());
+                        lines = results.iter().map(ToString::to_string).collect();
+                        chains = results;
+                    }
+                    Some(Err(err)) => {
+                        status = format!("scan failed: {}", err.as_str());
+                    }
+                    None => {}
+                }
+
+                ui.same_line(2.0 * (64.0 + 16.0));
+                ui.text(&status);
+
+                ui.dummy([0.0, 8.0]);
+
+                ui.text(im_str!("Results"));
+                ui.separator();
+
+                results::text_list(ui, im_str!("##results"), &lines, 250.0);
+
+                ui.input_text(im_str!("File"), &mut path)
+                    .resize_buffer(true)
+                    .build();
+
+                if ui.button(im_str!("Load"), [64.0, 26.0]) {
+                    match pointer::load(Path::new(path.to_str())) {
+                        Ok(results) => {
+                            status = format!("{} results loaded", results.len());
+                            lines = results.iter().map(ToString::to_string).collect();
+                            chains = results;
+                        }
+                        Err(err) => status = format!("load failed: {}", err.as_str()),
+                    }
+                }
+
+                ui.same_line(64.0 + 16.0);
+
+                if ui.button(im_str!("Save"), [64.0, 26.0]) {
+                    status = match pointer::save(Path::new(path.to_str()), &chains) {
+                        Ok(_) => format!("{} results saved", chains.len()),
+                        Err(err) => format!("save failed: {}", err.as_str()),
+                    };
+                }
+
+                ui.same_line(2.0 * (64.0 + 16.0));
+
+                if ui.button(im_str!("Copy All"), [64.0, 26.0]) {
+                    ui.set_clipboard_text(&ImString::new(lines.join("\n")));
+                }
+
+                ui.same_line(3.0 * (64.0 + 16.0));
+
+                if ui.button(im_str!("Close"), [64.0, 26.0]) {
+                    *run = false;
+                }
+            });
+    })
+}
@@ -0,0 +1,76 @@
+use imgui::*;
+use memflow::prelude::v1::*;
+
+/// Renders a scrollable list of addresses with a description.
+///
+/// Clicking an entry copies its address to the clipboard.
+/// Returns the index of the clicked entry.
+pub fn address_list(
+    ui: &Ui,
+    id: &ImStr,
+    results: &[(Address, String)],
+    height: f32,
+) -> Option<usize> {
+    let mut clicked = None;
+    ChildWindow::new(id)
+        .size([0.0, height])
+        .border(true)
+        .build(ui, || {
+            let mut clipper = ListClipper::new(results.len() as i32).begin(ui);
+            while clipper.step() {
+                for (idx, (address, description)) in results
+                    .iter()
+                    .enumerate()
+                    .take(clipper.display_end() as usize)
+                    .skip(clipper.display_start() as usize)
+                {
+                    let label =
+                        ImString::new(format!("{:016x}  {}", address.to_umem(), description));
+                    if Selectable::new(&label).build(ui) {
+                        ui.set_clipboard_text(&ImString::new(format!("{:x}", address.to_umem())));
+                        clicked = Some(idx);
+                    }
+                }
+            }
+        });
+    clicked
+}
+
+/// Copies all addresses of the list to the clipboard, one per line.
+pub fn copy_addresses(ui: &Ui, results: &[(Address, String)]) {
+    let text = results
+        .iter()
+        .map(|(address, _)| format!("{:x}", address.to_umem()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    ui.set_clipboard_text(&ImString::new(text));
+}
+
+/// Renders a scrollable list of text lines.
+///
+/// Clicking an entry copies it to the clipboard.
+pub fn text_list(ui: &Ui, id: &ImStr, lines: &[String], height: f32) {
+    ChildWindow::new(id)
+        .size([0.0, height])
+        .border(true)
+        .build(ui, || {
+            let mut clipper = ListClipper::new(lines.len() as i32).begin(ui);
+            while clipper.step() {
+                for line in lines
+                    .iter()
+                    .take(clipper.display_end() as usize)
+                    .skip(clipper.display_start() as usize)
+                {
+                    let label = ImString::new(line);
+                    if Selectable::new(&label).build(ui) {
+                        ui.set_clipboard_text(&label);
+                    }
+                }
+            }
+        });
+}
+
+/// Parses a hexadecimal number with an optional `0x` prefix as entered by the user.
+pub fn parse_hex(s: &str) -> Option<umem> {
+    umem::from_str_radix(s.trim().trim_start_matches("0x"), 16).ok()
+}
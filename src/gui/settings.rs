@@ -1,29 +1,114 @@
-use super::support;
+use super::connector_args::ArgsForm;
+use super::{results, support};
+use crate::memflow_wrapper::{self, ConnectionInfo};
+
+use std::sync::mpsc::{self, Receiver, TryRecvError};
 
 use imgui::*;
 use memflow::prelude::v1::*;
 use serde::{Deserialize, Serialize};
 
+/// Log levels in the order they are listed in the configuration dialog.
+const LOG_LEVELS: [&str; 6] = ["off", "error", "warn", "info", "debug", "trace"];
+
 // see https://github.com/serde-rs/serde/issues/368
 fn default_string_info() -> String {
     "info".to_string()
 }
+fn default_string_win32() -> String {
+    "win32".to_string()
+}
 fn default_bool_true() -> bool {
     true
 }
+fn default_log_max_size() -> u64 {
+    10 * 1024 * 1024
+}
+fn default_log_max_files() -> u32 {
+    3
+}
+fn default_section_gap() -> u64 {
+    0x4000_0000
+}
+fn default_section_start() -> String {
+    "0".to_string()
+}
+fn default_section_end() -> String {
+    "ffff000000000000".to_string()
+}
+fn default_section_max_count() -> u64 {
+    0x10000
+}
+fn default_section_timeout() -> u64 {
+    5000
+}
+fn default_section_refresh() -> u64 {
+    10000
+}
+fn default_watch_interval() -> u64 {
+    10
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub connector: String,
     #[serde(default)]
     pub args: String,
+    /// Name of the OS plugin, e.g. `win32` or `native`.
+    #[serde(default = "default_string_win32")]
+    pub os: String,
 
     #[serde(default = "default_string_info")]
     pub log_level: String,
+    /// Log level of the memflow crate, empty to use `log_level`.
+    #[serde(default)]
+    pub log_level_memflow: String,
+    /// Log level of the connector and OS plugins, empty to use `log_level`.
+    #[serde(default)]
+    pub log_level_connector: String,
+    /// Path of the log file, empty for the default destination.
+    #[serde(default)]
+    pub log_path: String,
+    /// Size in bytes after which the log file is rotated, 0 disables rotation.
+    #[serde(default = "default_log_max_size")]
+    pub log_max_size: u64,
+    /// Number of rotated log files which are kept.
+    #[serde(default = "default_log_max_files")]
+    pub log_max_files: u32,
 
     // TODO: expose caching options (lifetimes, etc)
     #[serde(default = "default_bool_true")]
     pub parse_sections: bool,
+    /// Mapped pages which are less than this amount of bytes apart are merged into one section.
+    #[serde(default = "default_section_gap")]
+    pub section_gap: u64,
+    /// Hex start address of the user mode range in which sections are enumerated.
+    #[serde(default = "default_section_start")]
+    pub section_start: String,
+    /// Hex end address of the user mode range in which sections are enumerated.
+    #[serde(default = "default_section_end")]
+    pub section_end: String,
+    #[serde(default)]
+    pub section_min_size: u64,
+    #[serde(default = "default_section_max_count")]
+    pub section_max_count: u64,
+    /// Time limit of the section enumeration in milliseconds.
+    #[serde(default = "default_section_timeout")]
+    pub section_timeout: u64,
+    /// Interval in milliseconds in which sections are enumerated in the background.
+    #[serde(default = "default_section_refresh")]
+    pub section_refresh: u64,
+
+    #[serde(default)]
+    pub symbol_store: String,
+
+    /// Interval in milliseconds in which the addresses watched by the debugger are read.
+    #[serde(default = "default_watch_interval")]
+    pub watch_interval: u64,
+
+    /// Opens the status window once memflow has been initialized.
+    #[serde(default)]
+    pub show_status: bool,
 }
 
 impl Default for Config {
@@ -31,10 +116,29 @@ impl Default for Config {
         Config {
             connector: String::new(),
             args: String::new(),
+            os: default_string_win32(),
 
             log_level: "info".to_string(),
+            log_level_memflow: String::new(),
+            log_level_connector: String::new(),
+            log_path: String::new(),
+            log_max_size: default_log_max_size(),
+            log_max_files: default_log_max_files(),
 
             parse_sections: false,
+            section_gap: default_section_gap(),
+            section_start: default_section_start(),
+            section_end: default_section_end(),
+            section_min_size: 0,
+            section_max_count: default_section_max_count(),
+            section_timeout: default_section_timeout(),
+            section_refresh: default_section_refresh(),
+
+            symbol_store: String::new(),
+
+            watch_interval: default_watch_interval(),
+
+            show_status: false,
         }
     }
 }
@@ -80,8 +184,10 @@ impl Settings {
     }
 
     /// Displays the configuration UI to the user and updates the config
-    /// This function blocks until the user clicks the "Ok" button.
-    pub fn configure(&mut self) {
+    /// This function blocks until the user clicks the "Load" or "Cancel" button.
+    ///
+    /// Returns `false` if the dialog was cancelled or closed, the config is left unchanged then.
+    pub fn configure(&mut self) -> bool {
         let inventory = Inventory::scan();
         let connectors: Vec<ImString> = inventory
             .available_connectors()
@@ -95,30 +201,64 @@ impl Settings {
             .find(|(_, c)| c.to_str() == self.config.connector)
             .map(|(i, _)| i as i32)
             .unwrap_or_default();
-        let mut connector_args = ImString::from(self.config.args.clone());
-        let mut log_level_idx = match self.config.log_level.to_lowercase().as_ref() {
-            "off" => 0,
-            "error" => 1,
-            "warn" => 2,
-            "info" => 3,
-            "debug" => 4,
-            "trace" => 5,
-            _ => 0,
+        let mut connector_args = ArgsForm::new(&self.config.args);
+        let os_plugins: Vec<ImString> = inventory
+            .available_os()
+            .iter()
+            .map(|os| ImString::from(os.to_owned()))
+            .collect::<Vec<_>>();
+        let mut os_idx = os_plugins
+            .iter()
+            .position(|os| os.to_str() == self.config.os)
+            .unwrap_or_default();
+        let mut log_level_idx = LOG_LEVELS
+            .iter()
+            .position(|level| *level == self.config.log_level.to_lowercase())
+            .unwrap_or_default();
+        // index 0 inherits the plugin log level
+        let source_level_idx = |level: &str| {
+            LOG_LEVELS
+                .iter()
+                .position(|l| *l == level.to_lowercase())
+                .map(|idx| idx + 1)
+                .unwrap_or_default()
         };
+        let mut log_level_memflow_idx = source_level_idx(&self.config.log_level_memflow);
+        let mut log_level_connector_idx = source_level_idx(&self.config.log_level_connector);
+        let mut log_path = ImString::from(self.config.log_path.clone());
+        let mut log_max_size = (self.config.log_max_size / 1024 / 1024).min(i32::MAX as u64) as i32;
+        let mut log_max_files = self.config.log_max_files.min(i32::MAX as u32) as i32;
         let mut parse_sections = self.config.parse_sections;
+        let mut section_gap = ImString::new(format!("{:x}", self.config.section_gap));
+        let mut section_start = ImString::from(self.config.section_start.clone());
+        let mut section_end = ImString::from(self.config.section_end.clone());
+        let mut section_min_size = ImString::new(format!("{:x}", self.config.section_min_size));
+        let mut section_max_count = self.config.section_max_count.min(i32::MAX as u64) as i32;
+        let mut section_timeout = self.config.section_timeout.min(i32::MAX as u64) as i32;
+        let mut section_refresh = self.config.section_refresh.min(i32::MAX as u64) as i32;
+        let mut symbol_store = ImString::from(self.config.symbol_store.clone());
+        let mut watch_interval = self.config.watch_interval.min(i32::MAX as u64) as i32;
+        let mut show_status = self.config.show_status;
+        let mut connection_test: Option<Receiver<Result<ConnectionInfo>>> = None;
+        let mut connection_status = String::new();
+        let mut loaded = false;
 
         {
-            support::show_window("memflow", 400.0, 290.0, |run, ui| {
+            support::show_window("memflow", 400.0, 820.0, |run, ui| {
                 let connectors_ref: Vec<&ImStr> =
                     connectors.iter().map(|c| c.as_ref()).collect::<Vec<_>>();
+                let os_plugins_ref: Vec<&ImStr> =
+                    os_plugins.iter().map(|os| os.as_ref()).collect::<Vec<_>>();
 
+                // fit the host window and scroll the options which do not fit into it
+                let [width, height] = ui.io().display_size;
                 Window::new(im_str!("memflow"))
                     .position([10.0, 10.0], Condition::Always)
-                    .size([375.0, 1000.0], Condition::Always)
+                    .size([width - 20.0, height - 20.0], Condition::Always)
                     .title_bar(false)
                     .resizable(false)
                     .movable(false)
-                    .scroll_bar(false)
+                    .scroll_bar(true)
                     .save_settings(false)
                     .focus_on_appearing(false)
                     .movable(false)
@@ -133,7 +273,16 @@ impl Settings {
                             4,
                         );
 
-                        ui.input_text(im_str!("Args"), &mut connector_args).build();
+                        if let Some(connector) = connectors.get(connector_idx as usize) {
+                            connector_args.set_connector(&inventory, connector.to_str());
+                        }
+                        connector_args.build(ui);
+
+                        ComboBox::new(im_str!("OS")).build_simple_string(
+                            ui,
+                            &mut os_idx,
+                            &os_plugins_ref[..],
+                        );
 
                         ui.dummy([0.0, 16.0]);
 
@@ -152,43 +301,188 @@ impl Settings {
                                 im_str!("Trace"),
                             ],
                         );
+                        let source_levels = [
+                            im_str!("Default"),
+                            im_str!("Off"),
+                            im_str!("Error"),
+                            im_str!("Warn"),
+                            im_str!("Info"),
+                            im_str!("Debug"),
+                            im_str!("Trace"),
+                        ];
+                        ComboBox::new(im_str!("memflow Log Level")).build_simple_string(
+                            ui,
+                            &mut log_level_memflow_idx,
+                            &source_levels,
+                        );
+                        ComboBox::new(im_str!("Connector Log Level")).build_simple_string(
+                            ui,
+                            &mut log_level_connector_idx,
+                            &source_levels,
+                        );
+                        ui.input_text(im_str!("Log File"), &mut log_path)
+                            .resize_buffer(true)
+                            .build();
+                        ui.input_int(im_str!("Max Log Size (MiB)"), &mut log_max_size)
+                            .build();
+                        ui.input_int(im_str!("Log Files Kept"), &mut log_max_files)
+                            .build();
 
                         ui.checkbox(im_str!("Parse Sections"), &mut parse_sections);
+                        ui.input_text(im_str!("Section Gap"), &mut section_gap)
+                            .resize_buffer(true)
+                            .build();
+                        ui.input_text(im_str!("Section Start"), &mut section_start)
+                            .resize_buffer(true)
+                            .build();
+                        ui.input_text(im_str!("Section End"), &mut section_end)
+                            .resize_buffer(true)
+                            .build();
+                        ui.input_text(im_str!("Min Section Size"), &mut section_min_size)
+                            .resize_buffer(true)
+                            .build();
+                        ui.input_int(im_str!("Max Sections"), &mut section_max_count)
+                            .build();
+                        ui.input_int(im_str!("Timeout (ms)"), &mut section_timeout)
+                            .build();
+                        ui.input_int(im_str!("Refresh (ms)"), &mut section_refresh)
+                            .build();
+
+                        ui.input_text(im_str!("Symbol Store"), &mut symbol_store)
+                            .resize_buffer(true)
+                            .build();
+                        ui.input_int(im_str!("Watch Interval (ms)"), &mut watch_interval)
+                            .build();
+
+                        ui.checkbox(im_str!("Show Status Window"), &mut show_status);
 
                         // TODO: configure caching
 
                         ui.dummy([0.0, 16.0]);
 
-                        if ui.button(im_str!("Load"), [64.0, 26.0]) {
+                        let args_valid = connector_args.errors().is_empty();
+                        // imgui cannot disable items, so the buttons are dimmed and their clicks
+                        // are ignored until the connection test finished
+                        let testing = connection_test.is_some();
+                        let dimmed = if testing {
+                            Some(ui.push_style_var(StyleVar::Alpha(0.5)))
+                        } else {
+                            None
+                        };
+
+                        if ui.button(im_str!("Load"), [64.0, 26.0]) && args_valid && !testing {
                             // update config
                             self.config.connector = connectors
                                 .get(connector_idx as usize)
                                 .map(|c| c.to_string())
                                 .unwrap_or_default();
-                            self.config.args = connector_args.to_str().to_owned();
-                            self.config.log_level = match log_level_idx {
-                                0 => "off",
-                                1 => "error",
-                                2 => "warn",
-                                3 => "info",
-                                4 => "debug",
-                                5 => "trace",
-                                _ => "off",
+                            self.config.args = connector_args.args();
+                            if let Some(os) = os_plugins.get(os_idx) {
+                                self.config.os = os.to_string();
                             }
-                            .to_string();
+                            self.config.log_level =
+                                LOG_LEVELS.get(log_level_idx).unwrap_or(&"off").to_string();
+                            let source_level = |idx: usize| {
+                                idx.checked_sub(1)
+                                    .and_then(|idx| LOG_LEVELS.get(idx))
+                                    .map(|level| level.to_string())
+                                    .unwrap_or_default()
+                            };
+                            self.config.log_level_memflow = source_level(log_level_memflow_idx);
+                            self.config.log_level_connector = source_level(log_level_connector_idx);
+                            self.config.log_path = log_path.to_str().trim().to_owned();
+                            self.config.log_max_size = log_max_size.max(0) as u64 * 1024 * 1024;
+                            self.config.log_max_files = log_max_files.max(0) as u32;
                             self.config.parse_sections = parse_sections;
+                            // invalid values keep the previous setting
+                            if let Some(gap) = results::parse_hex(section_gap.to_str()) {
+                                self.config.section_gap = gap;
+                            }
+                            if results::parse_hex(section_start.to_str()).is_some() {
+                                self.config.section_start = section_start.to_str().to_owned();
+                            }
+                            if results::parse_hex(section_end.to_str()).is_some() {
+                                self.config.section_end = section_end.to_str().to_owned();
+                            }
+                            if let Some(min_size) = results::parse_hex(section_min_size.to_str()) {
+                                self.config.section_min_size = min_size;
+                            }
+                            self.config.section_max_count = section_max_count.max(1) as u64;
+                            self.config.section_timeout = section_timeout.max(1) as u64;
+                            self.config.section_refresh = section_refresh.max(1) as u64;
+                            self.config.symbol_store = symbol_store.to_str().to_owned();
+                            self.config.watch_interval = watch_interval.max(1) as u64;
+                            self.config.show_status = show_status;
 
                             // close window
+                            loaded = true;
                             *run = false;
                         }
 
                         ui.same_line(64.0 + 16.0);
 
-                        if ui.button(im_str!("Cancel"), [64.0, 26.0]) {
+                        if ui.button(im_str!("Cancel"), [64.0, 26.0]) && !testing {
                             *run = false;
                         }
+
+                        ui.same_line(2.0 * (64.0 + 16.0));
+
+                        // the connector is built on a separate thread so the dialog stays responsive
+                        if ui.button(im_str!("Test"), [64.0, 26.0]) && args_valid && !testing {
+                            let mut config = self.config.clone();
+                            config.connector = connectors
+                                .get(connector_idx as usize)
+                                .map(|c| c.to_string())
+                                .unwrap_or_default();
+                            config.args = connector_args.args();
+                            if let Some(os) = os_plugins.get(os_idx) {
+                                config.os = os.to_string();
+                            }
+
+                            let (sender, receiver) = mpsc::channel();
+                            std::thread::spawn(move || {
+                                sender.send(memflow_wrapper::test_connection(&config)).ok();
+                            });
+                            connection_test = Some(receiver);
+                            connection_status = "connecting...".to_string();
+                        }
+                        if let Some(dimmed) = dimmed {
+                            dimmed.pop(ui);
+                        }
+
+                        if let Some(receiver) = &connection_test {
+                            match receiver.try_recv() {
+                                Ok(result) => {
+                                    connection_status = connection_summary(result);
+                                    connection_test = None;
+                                }
+                                Err(TryRecvError::Empty) => {}
+                                Err(TryRecvError::Disconnected) => {
+                                    connection_status = "connection test failed".to_string();
+                                    connection_test = None;
+                                }
+                            }
+                        }
+                        ui.text_wrapped(&ImString::new(&connection_status));
                     });
             });
         }
+
+        loaded
+    }
+}
+
+/// Describes the result of a connection test.
+fn connection_summary(result: Result<ConnectionInfo>) -> String {
+    match result {
+        Ok(info) => format!(
+            "connected\nkernel version: {}\nprocesses: {}\nphysical memory: {}",
+            info.kernel_version.as_deref().unwrap_or("unknown"),
+            info.process_count,
+            info.memory_size
+                .map(|size| format!("{:.2} GB", size as f64 / (1u64 << 30) as f64))
+                .unwrap_or_else(|| "unknown".to_string()),
+        ),
+        Err(err) => format!("unable to connect: {}", err),
     }
 }
@@ -0,0 +1,124 @@
+use super::{results, support};
+use crate::snapshot::{self, Snapshot};
+
+use imgui::*;
+use memflow::prelude::v1::*;
+
+/// Displays the snapshot diff window.
+///
+/// The window is initialized with the region at `address` with `size` bytes.
+/// The `snapshot` function is invoked with the region whenever the user takes a snapshot.
+/// Comparing takes a new snapshot and lists all values that changed since the baseline snapshot.
+/// This function blocks until the user closes the window.
+pub fn show<F: FnMut(Address, umem) -> Result<Snapshot>>(
+    address: Address,
+    size: umem,
+    mut snapshot: F,
+) {
+    let mut address = ImString::new(format!("{:x}", address.to_umem()));
+    let mut size = ImString::new(format!("{:x}", size));
+    let mut update_baseline = true;
+    let mut baseline: Option<Snapshot> = None;
+    let mut status = String::new();
+    let mut changes = Vec::new();
+
+    support::show_window("memflow - Snapshot Diff", 600.0, 460.0, |run, ui| {
+        Window::new(im_str!("Snapshot Diff"))
+            .position([10.0, 10.0], Condition::Always)
+            .size([575.0, 1000.0], Condition::Always)
+            .title_bar(false)
+            .resizable(false)
+            .movable(false)
+            .scroll_bar(false)
+            .save_settings(false)
+            .focus_on_appearing(false)
+            .build(ui, || {
+                ui.text(im_str!("Region"));
+                ui.separator();
+
+                ui.input_text(im_str!("Address"), &mut address)
+                    .resize_buffer(true)
+                    .build();
+                ui.input_text(im_str!("Size"), &mut size)
+                    .resize_buffer(true)
+                    .build();
+                ui.checkbox(im_str!("Update Baseline"), &mut update_baseline);
+
+                let region =
+                    results::parse_hex(address.to_str()).zip(results::parse_hex(size.to_str()));
+
+                if ui.button(im_str!("Snapshot"), [80.0, 26.0]) {
+                    match region.map(|(address, size)| snapshot(address.into(), size)) {
+                        Some(Ok(current)) => {
+                            status = format!("baseline of {} bytes taken", current.size());
+                            baseline = Some(current);
+                            changes.clear();
+                        }
+                        Some(Err(err)) => status = format!("snapshot failed: {}", err.as_str()),
+                        None => status = "invalid address or size".to_string(),
+                    }
+                }
+
+                ui.same_line(80.0 + 16.0);
+
+                if ui.button(im_str!("Compare"), [80.0, 26.0]) {
+                    match (&baseline, region) {
+                        (Some(old), Some((address, size))) => {
+                            match snapshot(address.into(), size).and_then(|current| {
+                                snapshot::diff(old, &current).map(|diff| (current, diff))
+                            }) {
+                                Ok((current, diff)) => {
+                                    status = format!(
+                                        "{} changes in {:.1}s",
+                                        diff.len(),
+                                        current.time().duration_since(old.time()).as_secs_f32()
+                                    );
+                                    changes = diff
+                                        .iter()
+                                        .map(|change| {
+                                            (
+                                                current.base() + change.offset,
+                                                format!(
+                                                    "+{:04x}  {:8} {} -> {}",
+                                                    change.offset,
+                                                    change.guess.name(),
+                                                    change.old_value(),
+                                                    change.new_value()
+                                                ),
+                                            )
+                                        })
+                                        .collect();
+                                    if update_baseline {
+                                        baseline = Some(current);
+                                    }
+                                }
+                                Err(err) => status = format!("compare failed: {}", err.as_str()),
+                            }
+                        }
+                        (None, _) => status = "take a baseline snapshot first".to_string(),
+                        (_, None) => status = "invalid address or size".to_string(),
+                    }
+                }
+
+                ui.same_line(2.0 * (80.0 + 16.0));
+                ui.text(&status);
+
+                ui.dummy([0.0, 8.0]);
+
+                ui.text(im_str!("Changes"));
+                ui.separator();
+
+                results::address_list(ui, im_str!("##changes"), &changes, 250.0);
+
+                if ui.button(im_str!("Copy All"), [64.0, 26.0]) {
+                    results::copy_addresses(ui, &changes);
+                }
+
+                ui.same_line(64.0 + 16.0);
+
+                if ui.button(im_str!("Close"), [64.0, 26.0]) {
+                    *run = false;
+                }
+            });
+    })
+}
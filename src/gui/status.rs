@@ -0,0 +1,126 @@
+use super::support;
+use crate::stats::{self, Snapshot};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use imgui::*;
+
+/// Interval in which the rates and the memflow state are refreshed.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The state of memflow which is shown in the status window.
+pub struct StatusInfo {
+    pub connector: String,
+    pub os: String,
+    /// Handles of the kernel and all opened processes.
+    pub handles: Vec<u32>,
+}
+
+/// Displays a small status window with the connection and the access statistics.
+///
+/// `status` is invoked once per refresh interval to read the state of memflow.
+/// This function blocks until the user closes the window or `running` is cleared.
+pub fn show<F: FnMut() -> Option<StatusInfo>>(running: &AtomicBool, mut status: F) {
+    let mut info = status();
+    let mut last_refresh = Instant::now();
+    let mut last_snapshot = stats::snapshot();
+    let mut interval = Snapshot::default();
+    let mut interval_secs = REFRESH_INTERVAL.as_secs_f64();
+
+    support::show_window("memflow - Status", 320.0, 300.0, |run, ui| {
+        if !running.load(Ordering::Relaxed) {
+            *run = false;
+            return;
+        }
+
+        if last_refresh.elapsed() >= REFRESH_INTERVAL {
+            let snapshot = stats::snapshot();
+            interval = snapshot.since(&last_snapshot);
+            interval_secs = last_refresh.elapsed().as_secs_f64();
+            last_snapshot = snapshot;
+            last_refresh = Instant::now();
+            info = status();
+        }
+        let total = last_snapshot;
+        let per_second = |count: u64| count as f64 / interval_secs;
+
+        Window::new(im_str!("Status"))
+            .position([10.0, 10.0], Condition::Always)
+            .size([295.0, 1000.0], Condition::Always)
+            .title_bar(false)
+            .resizable(false)
+            .movable(false)
+            .scroll_bar(false)
+            .save_settings(false)
+            .focus_on_appearing(false)
+            .build(ui, || {
+                ui.text(im_str!("Connection"));
+                ui.separator();
+
+                match &info {
+                    Some(info) => {
+                        ui.text(format!("Connector: {}", info.connector));
+                        ui.text(format!("OS: {}", info.os));
+                        let handles = info
+                            .handles
+                            .iter()
+                            .map(|handle| handle.to_string())
+                            .collect::<Vec<_>>();
+                        ui.text_wrapped(&ImString::new(format!("Handles: {}", handles.join(", "))));
+                    }
+                    None => ui.text("memflow is not initialized"),
+                }
+
+                ui.dummy([0.0, 8.0]);
+
+                ui.text(im_str!("Statistics"));
+                ui.separator();
+
+                ui.text(format!(
+                    "Reads: {:.0}/s ({} total)",
+                    per_second(interval.reads),
+                    total.reads
+                ));
+                ui.text(format!(
+                    "Failed reads: {:.0}/s ({} total)",
+                    per_second(interval.failed_reads),
+                    total.failed_reads
+                ));
+                ui.text(format!(
+                    "Read latency: {}",
+                    format_latency(interval.read_latency())
+                ));
+                ui.text(format!(
+                    "Writes: {:.0}/s ({} total, {} failed)",
+                    per_second(interval.writes),
+                    total.writes,
+                    total.failed_writes
+                ));
+                ui.text(format!(
+                    "Enumerations: {} (avg. {})",
+                    total.enumerations,
+                    format_latency(total.enumeration_latency())
+                ));
+                ui.text(format!(
+                    "Section cache hits: {}",
+                    total
+                        .section_cache_hit_ratio()
+                        .map(|ratio| format!("{:.1}%", ratio * 100.0))
+                        .unwrap_or_else(|| "-".to_string())
+                ));
+            });
+    });
+
+    running.store(false, Ordering::Relaxed);
+}
+
+fn format_latency(latency: Option<Duration>) -> String {
+    match latency {
+        Some(latency) if latency >= Duration::from_millis(1) => {
+            format!("{:.2} ms", latency.as_secs_f64() * 1000.0)
+        }
+        Some(latency) => format!("{:.1} µs", latency.as_secs_f64() * 1_000_000.0),
+        None => "-".to_string(),
+    }
+}
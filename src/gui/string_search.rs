@@ -0,0 +1,122 @@
+use super::{results, support};
+
+use imgui::*;
+use memflow::prelude::v1::*;
+
+/// Displays the string search and cross-reference window.
+///
+/// The `search` function is invoked with the string and the enabled encodings (ascii, utf-16)
+/// whenever the user starts a string search.
+/// The `references` function is invoked with the target address and the maximum offset
+/// and returns all pointers to the target.
+/// Clicking a found string selects it as the target of the reference search.
+/// This function blocks until the user closes the window.
+pub fn show<S, R>(mut search: S, mut references: R)
+where
+    S: FnMut(&str, bool, bool) -> Result<Vec<(Address, String)>>,
+    R: FnMut(Address, umem) -> Result<Vec<(Address, String)>>,
+{
+    let mut text = ImString::with_capacity(256);
+    let mut ascii = true;
+    let mut utf16 = true;
+    let mut string_status = String::new();
+    let mut string_results = Vec::new();
+
+    let mut address = ImString::with_capacity(32);
+    let mut max_offset = ImString::new("0");
+    let mut reference_status = String::new();
+    let mut reference_results = Vec::new();
+
+    support::show_window("memflow - String Search", 600.0, 640.0, |run, ui| {
+        Window::new(im_str!("String Search"))
+            .position([10.0, 10.0], Condition::Always)
+            .size([575.0, 1000.0], Condition::Always)
+            .title_bar(false)
+            .resizable(false)
+            .movable(false)
+            .scroll_bar(false)
+            .save_settings(false)
+            .focus_on_appearing(false)
+            .build(ui, || {
+                ui.text(im_str!("Strings"));
+                ui.separator();
+
+                ui.input_text(im_str!("String"), &mut text)
+                    .resize_buffer(true)
+                    .build();
+                ui.checkbox(im_str!("ASCII"), &mut ascii);
+                ui.same_line(100.0);
+                ui.checkbox(im_str!("UTF-16"), &mut utf16);
+
+                if ui.button(im_str!("Search"), [64.0, 26.0]) {
+                    match search(text.to_str(), ascii, utf16) {
+                        Ok(results) => {
+                            string_status = format!("{} results", results.len());
+                            string_results = results;
+                        }
+                        Err(err) => {
+                            string_status = format!("search failed: {}", err.as_str());
+                            string_results.clear();
+                        }
+                    }
+                }
+
+                ui.same_line(64.0 + 16.0);
+                ui.text(&string_status);
+
+                if let Some(idx) =
+                    results::address_list(ui, im_str!("##strings"), &string_results, 150.0)
+                {
+                    address = ImString::new(format!("{:x}", string_results[idx].0.to_umem()));
+                }
+
+                ui.dummy([0.0, 8.0]);
+
+                ui.text(im_str!("References"));
+                ui.separator();
+
+                ui.input_text(im_str!("Address"), &mut address)
+                    .resize_buffer(true)
+                    .build();
+                ui.input_text(im_str!("Max Offset"), &mut max_offset)
+                    .resize_buffer(true)
+                    .build();
+
+                if ui.button(im_str!("Find"), [64.0, 26.0]) {
+                    match (
+                        results::parse_hex(address.to_str()),
+                        results::parse_hex(max_offset.to_str()),
+                    ) {
+                        (Some(target), Some(max_offset)) => {
+                            match references(target.into(), max_offset) {
+                                Ok(results) => {
+                                    reference_status = format!("{} results", results.len());
+                                    reference_results = results;
+                                }
+                                Err(err) => {
+                                    reference_status = format!("search failed: {}", err.as_str());
+                                    reference_results.clear();
+                                }
+                            }
+                        }
+                        _ => reference_status = "invalid address or offset".to_string(),
+                    }
+                }
+
+                ui.same_line(64.0 + 16.0);
+                ui.text(&reference_status);
+
+                results::address_list(ui, im_str!("##references"), &reference_results, 150.0);
+
+                if ui.button(im_str!("Copy All"), [64.0, 26.0]) {
+                    results::copy_addresses(ui, &reference_results);
+                }
+
+                ui.same_line(64.0 + 16.0);
+
+                if ui.button(im_str!("Close"), [64.0, 26.0]) {
+                    *run = false;
+                }
+            });
+    })
+}
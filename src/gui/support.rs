@@ -2,6 +2,10 @@ use glium::glutin;
 use glium::glutin::event::{Event, WindowEvent};
 use glium::glutin::event_loop::{ControlFlow, EventLoop};
 use glium::glutin::platform::run_return::EventLoopExtRunReturn;
+#[cfg(target_os = "linux")]
+use glium::glutin::platform::unix::EventLoopExtUnix;
+#[cfg(windows)]
+use glium::glutin::platform::windows::EventLoopExtWindows;
 use glium::glutin::window::WindowBuilder;
 use glium::{Display, Surface};
 use imgui::{Context, FontConfig, FontGlyphRanges, FontSource, Ui};
@@ -11,6 +15,18 @@ use std::time::Instant;
 
 const CLEAR_COLOR: [f32; 4] = [0.25, 0.25, 0.25, 1.0];
 
+/// Creates an event loop which may also be used outside of the main thread,
+/// e.g. by the status window which runs alongside ReClass.NET.
+#[cfg(any(windows, target_os = "linux"))]
+fn new_event_loop() -> EventLoop<()> {
+    EventLoop::new_any_thread()
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+fn new_event_loop() -> EventLoop<()> {
+    EventLoop::new()
+}
+
 pub fn show_window<F: FnMut(&mut bool, &mut Ui)>(
     title: &str,
     width: f32,
@@ -21,7 +37,7 @@ pub fn show_window<F: FnMut(&mut bool, &mut Ui)>(
         Some(idx) => title.split_at(idx + 1).1,
         None => title,
     };
-    let mut event_loop = EventLoop::new();
+    let mut event_loop = new_event_loop();
     let context = glutin::ContextBuilder::new().with_vsync(true);
     let builder = WindowBuilder::new()
         .with_title(title.to_owned())
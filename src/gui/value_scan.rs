@@ -0,0 +1,119 @@
+use super::{results, support};
+use crate::scan::value::{CompareType, ValueType};
+
+use std::borrow::Cow;
+
+use imgui::*;
+use memflow::prelude::v1::*;
+
+/// Displays the value scanner window.
+///
+/// The `first` function is invoked with the value type, the comparison and both entered values
+/// whenever the user starts a new scan, `next` refines the results of the previous scan
+/// and `reset` discards them.
+/// Both scan functions return the (possibly truncated) list of results with their current values.
+/// This function blocks until the user closes the window.
+pub fn show<F, N, R>(mut first: F, mut next: N, mut reset: R)
+where
+    F: FnMut(ValueType, CompareType, &str, &str) -> Result<(usize, Vec<(Address, String)>)>,
+    N: FnMut(CompareType, &str, &str) -> Result<(usize, Vec<(Address, String)>)>,
+    R: FnMut(),
+{
+    let mut value_type = 2;
+    let mut compare_type = 0;
+    let mut value = ImString::with_capacity(256);
+    let mut value2 = ImString::with_capacity(256);
+    let mut status = String::new();
+    let mut scan_results = Vec::new();
+
+    support::show_window("memflow - Value Scanner", 600.0, 520.0, |run, ui| {
+        Window::new(im_str!("Value Scanner"))
+            .position([10.0, 10.0], Condition::Always)
+            .size([575.0, 1000.0], Condition::Always)
+            .title_bar(false)
+            .resizable(false)
+            .movable(false)
+            .scroll_bar(false)
+            .save_settings(false)
+            .focus_on_appearing(false)
+            .build(ui, || {
+                ui.text(im_str!("Value"));
+                ui.separator();
+
+                ComboBox::new(im_str!("Value Type")).build_simple(
+                    ui,
+                    &mut value_type,
+                    &ValueType::ALL,
+                    &|ty| Cow::Owned(ImString::new(ty.name())),
+                );
+                ComboBox::new(im_str!("Scan Type")).build_simple(
+                    ui,
+                    &mut compare_type,
+                    &CompareType::ALL,
+                    &|compare| Cow::Owned(ImString::new(compare.name())),
+                );
+                ui.input_text(im_str!("Value"), &mut value)
+                    .resize_buffer(true)
+                    .build();
+                ui.input_text(im_str!("Upper Bound"), &mut value2)
+                    .resize_buffer(true)
+                    .build();
+
+                let ty = ValueType::ALL[value_type];
+                let compare = CompareType::ALL[compare_type];
+
+                let result = if ui.button(im_str!("First Scan"), [80.0, 26.0]) {
+                    Some(first(ty, compare, value.to_str(), value2.to_str()))
+                } else {
+                    None
+                };
+
+                ui.same_line(80.0 + 16.0);
+
+                let result = if ui.button(im_str!("Next Scan"), [80.0, 26.0]) {
+                    Some(next(compare, value.to_str(), value2.to_str()))
+                } else {
+                    result
+                };
+
+                ui.same_line(2.0 * (80.0 + 16.0));
+
+                if ui.button(im_str!("Reset"), [80.0, 26.0]) {
+                    reset();
+                    status.clear();
+                    scan_results.clear();
+                }
+
+                match result {
+                    Some(Ok((count, results))) => {
+                        status = format!("{} results", count);
+                        scan_results = results;
+                    }
+                    Some(Err(err)) => {
+                        status = format!("scan failed: {}", err.as_str());
+                    }
+                    None => {}
+                }
+
+                ui.same_line(3.0 * (80.0 + 16.0));
+                ui.text(&status);
+
+                ui.dummy([0.0, 8.0]);
+
+                ui.text(im_str!("Results"));
+                ui.separator();
+
+                results::address_list(ui, im_str!("##results"), &scan_results, 250.0);
+
+                if ui.button(im_str!("Copy All"), [64.0, 26.0]) {
+                    results::copy_addresses(ui, &scan_results);
+                }
+
+                ui.same_line(64.0 + 16.0);
+
+                if ui.button(im_str!("Close"), [64.0, 26.0]) {
+                    *run = false;
+                }
+            });
+    })
+}
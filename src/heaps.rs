@@ -0,0 +1,236 @@
+use memflow::prelude::v1::*;
+
+use crate::symbols::SymbolCache;
+
+/// Upper bound of heaps read from the PEB.
+const MAX_HEAPS: u32 = 0x400;
+/// Upper bound of segments walked per heap to protect against corrupted lists.
+const MAX_SEGMENTS: usize = 0x1000;
+
+/// `_HEAP.Signature` of NT heaps.
+const NT_HEAP_SIGNATURE: u32 = 0xEEFF_EEFF;
+/// `_SEGMENT_HEAP.Signature` of segment heaps.
+const SEGMENT_HEAP_SIGNATURE: u32 = 0xDDEE_DDEE;
+
+/// Offsets into the heap structures of a process.
+///
+/// The offsets change between Windows builds and are taken from the ntoskrnl PDB
+/// which also contains the user mode heap types.
+pub struct HeapOffsets {
+    /// `_EPROCESS.Peb`
+    peb: umem,
+    /// `_PEB.NumberOfHeaps`
+    number_of_heaps: umem,
+    /// `_PEB.ProcessHeaps`
+    process_heaps: umem,
+    /// `_HEAP.Signature`
+    heap_signature: umem,
+    /// `_HEAP.SegmentList`
+    heap_segment_list: umem,
+    /// `_HEAP_SEGMENT.SegmentListEntry`
+    segment_list_entry: umem,
+    /// `_HEAP_SEGMENT.BaseAddress`
+    segment_base: umem,
+    /// `_HEAP_SEGMENT.LastValidEntry`
+    segment_end: umem,
+    /// segment heaps are only available on Windows 10 and newer
+    segment_heap: Option<SegmentHeapOffsets>,
+}
+
+struct SegmentHeapOffsets {
+    /// `_SEGMENT_HEAP.Signature`
+    signature: umem,
+    /// `_SEGMENT_HEAP.SegContexts`
+    seg_contexts: umem,
+    /// number of elements in `_SEGMENT_HEAP.SegContexts`
+    seg_context_count: u64,
+    /// size of `_HEAP_SEG_CONTEXT`
+    seg_context_size: umem,
+    /// `_HEAP_SEG_CONTEXT.SegmentShift`
+    segment_shift: umem,
+    /// `_HEAP_SEG_CONTEXT.SegmentListHead`
+    segment_list_head: umem,
+    /// `_HEAP_PAGE_SEGMENT.ListEntry`
+    page_segment_list_entry: umem,
+}
+
+impl HeapOffsets {
+    pub fn new(symbols: &SymbolCache) -> Option<Self> {
+        Some(Self {
+            peb: symbols.field_offset("_EPROCESS", "Peb")?,
+            number_of_heaps: symbols.field_offset("_PEB", "NumberOfHeaps")?,
+            process_heaps: symbols.field_offset("_PEB", "ProcessHeaps")?,
+            heap_signature: symbols.field_offset("_HEAP", "Signature")?,
+            heap_segment_list: symbols.field_offset("_HEAP", "SegmentList")?,
+            segment_list_entry: symbols.field_offset("_HEAP_SEGMENT", "SegmentListEntry")?,
+            segment_base: symbols.field_offset("_HEAP_SEGMENT", "BaseAddress")?,
+            segment_end: symbols.field_offset("_HEAP_SEGMENT", "LastValidEntry")?,
+            segment_heap: SegmentHeapOffsets::new(symbols),
+        })
+    }
+
+    /// Offset of the PEB pointer in the `_EPROCESS` of a process.
+    pub fn peb(&self) -> umem {
+        self.peb
+    }
+}
+
+impl SegmentHeapOffsets {
+    fn new(symbols: &SymbolCache) -> Option<Self> {
+        let (seg_contexts, seg_contexts_ty) = symbols.field("_SEGMENT_HEAP", "SegContexts")?;
+        let seg_context_size = symbols.type_layout("_HEAP_SEG_CONTEXT")?.size;
+        Some(Self {
+            signature: symbols.field_offset("_SEGMENT_HEAP", "Signature")?,
+            seg_contexts,
+            seg_context_count: seg_contexts_ty.size() / seg_context_size.max(1),
+            seg_context_size,
+            segment_shift: symbols.field_offset("_HEAP_SEG_CONTEXT", "SegmentShift")?,
+            segment_list_head: symbols.field_offset("_HEAP_SEG_CONTEXT", "SegmentListHead")?,
+            page_segment_list_entry: symbols.field_offset("_HEAP_PAGE_SEGMENT", "ListEntry")?,
+        })
+    }
+}
+
+/// A memory range owned by one of the process heaps.
+#[derive(Clone, Debug)]
+pub struct HeapRegion {
+    /// Index of the heap in `_PEB.ProcessHeaps`.
+    pub index: usize,
+    pub base: Address,
+    pub size: umem,
+}
+
+/// Lists the segments of all heaps in the `_PEB.ProcessHeaps` array of the PEB at `peb`.
+///
+/// Both NT heaps and segment heaps are supported. Only the native heaps are found,
+/// the heaps of the 32 bit PEB of wow64 processes are not walked.
+pub fn enumerate<T: MemoryView>(
+    mem: &mut T,
+    arch: ArchitectureObj,
+    offsets: &HeapOffsets,
+    peb: Address,
+) -> Vec<HeapRegion> {
+    let count = mem
+        .read::<u32>(peb + offsets.number_of_heaps)
+        .data_part()
+        .unwrap_or_default()
+        .min(MAX_HEAPS);
+    let heaps = match mem
+        .read_addr_arch(arch, peb + offsets.process_heaps)
+        .data_part()
+    {
+        Ok(heaps) if !heaps.is_null() => heaps,
+        _ => return Vec::new(),
+    };
+
+    let pointer_size = arch.size_addr();
+    let mut regions = Vec::new();
+    for index in 0..count as usize {
+        let heap = match mem
+            .read_addr_arch(arch, heaps + index * pointer_size)
+            .data_part()
+        {
+            Ok(heap) if !heap.is_null() => heap,
+            _ => continue,
+        };
+
+        let segments = match &offsets.segment_heap {
+            Some(segment_heap)
+                if signature(mem, heap + segment_heap.signature)
+                    == Some(SEGMENT_HEAP_SIGNATURE) =>
+            {
+                segment_heap_segments(mem, arch, segment_heap, heap)
+            }
+            _ if signature(mem, heap + offsets.heap_signature) == Some(NT_HEAP_SIGNATURE) => {
+                nt_heap_segments(mem, arch, offsets, heap)
+            }
+            _ => {
+                log::debug!("unknown heap signature at {:x}", heap);
+                continue;
+            }
+        };
+
+        regions.extend(
+            segments
+                .into_iter()
+                .map(|(base, size)| HeapRegion { index, base, size }),
+        );
+    }
+    regions
+}
+
+fn signature<T: MemoryView>(mem: &mut T, address: Address) -> Option<u32> {
+    mem.read::<u32>(address).data_part().ok()
+}
+
+/// Walks `_HEAP.SegmentList` of an NT heap.
+fn nt_heap_segments<T: MemoryView>(
+    mem: &mut T,
+    arch: ArchitectureObj,
+    offsets: &HeapOffsets,
+    heap: Address,
+) -> Vec<(Address, umem)> {
+    let mut segments = Vec::new();
+    walk_list(mem, arch, heap + offsets.heap_segment_list, |mem, entry| {
+        let segment = entry - offsets.segment_list_entry;
+        let base = mem.read_addr_arch(arch, segment + offsets.segment_base);
+        let end = mem.read_addr_arch(arch, segment + offsets.segment_end);
+        if let (Ok(base), Ok(end)) = (base.data_part(), end.data_part()) {
+            if !base.is_null() && base < end {
+                segments.push((base, (end - base) as umem));
+            }
+        }
+    });
+    segments
+}
+
+/// Walks the page segments of all `_SEGMENT_HEAP.SegContexts` of a segment heap.
+fn segment_heap_segments<T: MemoryView>(
+    mem: &mut T,
+    arch: ArchitectureObj,
+    offsets: &SegmentHeapOffsets,
+    heap: Address,
+) -> Vec<(Address, umem)> {
+    let mut segments = Vec::new();
+    for idx in 0..offsets.seg_context_count {
+        let context = heap + offsets.seg_contexts + idx * offsets.seg_context_size;
+
+        // all segments of a context share the same power of two size
+        let size = match mem.read::<u8>(context + offsets.segment_shift).data_part() {
+            Ok(shift) if (12..48).contains(&shift) => 1 << shift,
+            _ => continue,
+        };
+
+        walk_list(
+            mem,
+            arch,
+            context + offsets.segment_list_head,
+            |_, entry| {
+                segments.push((entry - offsets.page_segment_list_entry, size));
+            },
+        );
+    }
+    segments
+}
+
+/// Invokes `f` for each entry of the doubly linked `LIST_ENTRY` list at `head`.
+fn walk_list<T: MemoryView, F: FnMut(&mut T, Address)>(
+    mem: &mut T,
+    arch: ArchitectureObj,
+    head: Address,
+    mut f: F,
+) {
+    let mut count = 0;
+    let mut entry = mem
+        .read_addr_arch(arch, head)
+        .data_part()
+        .unwrap_or_default();
+    while !entry.is_null() && entry != head && count < MAX_SEGMENTS {
+        f(mem, entry);
+        entry = mem
+            .read_addr_arch(arch, entry)
+            .data_part()
+            .unwrap_or_default();
+        count += 1;
+    }
+}
@@ -0,0 +1,149 @@
+use memflow::prelude::v1::*;
+
+use crate::memflow_wrapper::Target;
+use crate::reclass::SectionCategory;
+use crate::symbols::{database::FieldType, SymbolCache};
+
+/// Offsets of the dynamic system address space regions in `MiState`.
+///
+/// Since Windows 10 the kernel assigns the pools, system PTEs and other regions
+/// randomized address ranges which are stored in `MiState.Vs.SystemVaRegions`.
+pub struct SystemVaOffsets {
+    /// address of `MiState.Vs.SystemVaRegions`
+    regions: Address,
+    /// number of elements in `SystemVaRegions`
+    count: u64,
+    /// size of `_MI_SYSTEM_VA_ASSIGNMENT`
+    entry_size: umem,
+    /// `_MI_SYSTEM_VA_ASSIGNMENT.BaseAddress`
+    base_address: umem,
+    /// `_MI_SYSTEM_VA_ASSIGNMENT.NumberOfBytes`
+    number_of_bytes: umem,
+}
+
+impl SystemVaOffsets {
+    pub fn new(symbols: &SymbolCache) -> Option<Self> {
+        let mi_state = symbols.address_by_symbol("ntoskrnl!MiState")?;
+        let (regions, ty) = symbols.field("_MI_SYSTEM_INFORMATION", "Vs.SystemVaRegions")?;
+        let count = match ty {
+            FieldType::Array(_, count) => *count,
+            _ => return None,
+        };
+        Some(Self {
+            regions: mi_state + regions,
+            count,
+            entry_size: symbols.type_layout("_MI_SYSTEM_VA_ASSIGNMENT")?.size,
+            base_address: symbols.field_offset("_MI_SYSTEM_VA_ASSIGNMENT", "BaseAddress")?,
+            number_of_bytes: symbols.field_offset("_MI_SYSTEM_VA_ASSIGNMENT", "NumberOfBytes")?,
+        })
+    }
+}
+
+/// Kernel address of `KUSER_SHARED_DATA` on 64 bit Windows.
+const KUSER_SHARED_DATA: umem = 0xFFFF_F780_0000_0000;
+/// `KUSER_SHARED_DATA.NtMajorVersion`
+const NT_MAJOR_VERSION: umem = 0x26C;
+/// `KUSER_SHARED_DATA.NtMinorVersion`
+const NT_MINOR_VERSION: umem = 0x270;
+
+/// Reads the version of the Windows kernel, e.g. `10.0.19045`.
+///
+/// The major and minor version are read from `KUSER_SHARED_DATA`,
+/// the build number from the `NtBuildNumber` export of ntoskrnl.
+pub fn windows_version(mut kernel: &mut dyn Target) -> Option<String> {
+    let major = kernel
+        .read::<u32>((KUSER_SHARED_DATA + NT_MAJOR_VERSION).into())
+        .data_part()
+        .ok()?;
+    let minor = kernel
+        .read::<u32>((KUSER_SHARED_DATA + NT_MINOR_VERSION).into())
+        .data_part()
+        .ok()?;
+
+    // ntoskrnl is always the first entry of the loaded module list
+    let ntoskrnl = kernel.modules().ok()?.into_iter().next()?;
+    let build_number = kernel
+        .module_exports(&ntoskrnl)
+        .ok()?
+        .into_iter()
+        .find(|export| export.name.as_ref() == "NtBuildNumber")?;
+    // the upper bits mark free and checked builds
+    let build = kernel
+        .read::<u32>(ntoskrnl.base + build_number.offset)
+        .data_part()
+        .ok()?
+        & 0xFFFF;
+
+    Some(format!("{}.{}.{}", major, minor, build))
+}
+
+/// A dynamically assigned region of the system address space.
+#[derive(Clone, Debug)]
+pub struct SystemRegion {
+    pub base: Address,
+    pub size: umem,
+    pub category: SectionCategory,
+    pub name: String,
+}
+
+/// Reads all assigned regions from `MiState.Vs.SystemVaRegions`.
+pub fn system_regions<T: MemoryView>(
+    kernel: &mut T,
+    arch: ArchitectureObj,
+    offsets: &SystemVaOffsets,
+) -> Vec<SystemRegion> {
+    let mut regions = Vec::new();
+    for idx in 0..offsets.count {
+        let entry = offsets.regions + idx * offsets.entry_size;
+        let base = kernel
+            .read_addr_arch(arch, entry + offsets.base_address)
+            .data_part();
+        let size = kernel
+            .read::<u64>(entry + offsets.number_of_bytes)
+            .data_part();
+        if let (Ok(base), Ok(size)) = (base, size) {
+            if !base.is_null() && size != 0 {
+                let (name, category) = region_type(idx);
+                regions.push(SystemRegion {
+                    base,
+                    size: size as umem,
+                    category,
+                    name,
+                });
+            }
+        }
+    }
+    regions
+}
+
+/// Returns the name and category of a `_MI_SYSTEM_VA_TYPE` value.
+///
+/// The enum is not part of the type information that is loaded from the PDB,
+/// the values have been stable since their introduction in Windows 10.
+fn region_type(idx: u64) -> (String, SectionCategory) {
+    let (name, category) = match idx {
+        1 => ("Session", SectionCategory::Data),
+        2 => ("Process", SectionCategory::Data),
+        3 => ("Boot Loaded", SectionCategory::Data),
+        4 => ("PFN Database", SectionCategory::Data),
+        5 => ("NonPaged Pool", SectionCategory::Heap),
+        6 => ("Paged Pool", SectionCategory::Heap),
+        7 => ("Special Pool", SectionCategory::Heap),
+        8 => ("System Cache", SectionCategory::Data),
+        9 => ("System PTEs", SectionCategory::Data),
+        10 => ("HAL", SectionCategory::Data),
+        11 => ("Session Global", SectionCategory::Data),
+        12 => ("Driver Images", SectionCategory::Data),
+        _ => return (format!("System VA {}", idx), SectionCategory::Data),
+    };
+    (name.to_string(), category)
+}
+
+/// Guesses whether a PE section of a driver contains code by its name.
+///
+/// Drivers place pageable code in `PAGE*` sections and discardable code in `INIT`.
+pub fn is_code_section(name: &str) -> bool {
+    name.starts_with(".text")
+        || name == "INIT"
+        || (name.starts_with("PAGE") && !name.contains("DATA") && !name.contains("CONST"))
+}
@@ -4,26 +4,105 @@ use reclass::*;
 mod memflow_wrapper;
 use memflow_wrapper::*;
 
+mod debugger;
+use debugger::WriteWatch;
+
 mod gui;
 
+mod heaps;
+
+mod kernel;
+
+mod linux;
+
+mod logging;
+
+mod rtti;
+
+mod section_cache;
+
+mod sections;
+use sections::SectionOptions;
+
+mod snapshot;
+use snapshot::Snapshot;
+
+mod stats;
+
+mod symbols;
+use symbols::{database::FieldType, SymbolCache};
+
+mod threads;
+
+mod rcnet;
+use rcnet::Project;
+
+mod scan;
+use scan::{
+    pattern::Pattern,
+    pointer::{PointerChain, PointerScanOptions},
+    string::StringEncoding,
+    value::{CompareType, Condition as ValueCondition, ValueScan, ValueType},
+};
+
 use std::ffi::c_void;
 use std::ptr;
 use std::slice;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
 use memflow::prelude::v1::*;
 
-const NTOSKRNL_PID: ProcessId = 4;
-const NTOSKRNL_HANDLE: ProcessHandle = 4 as ProcessHandle;
+const KERNEL_PID: ProcessId = KERNEL_HANDLE as ProcessId;
+
+fn process_not_found() -> Error {
+    Error(ErrorOrigin::Other, ErrorKind::ProcessNotFound)
+}
+
+/// Clones the target of a handle together with the configured section limits.
+///
+/// Scans run on the clone so memflow is not locked while the memory of the target is read.
+fn scan_target(handle: ProcessHandle) -> Result<(Box<dyn Target>, SectionOptions)> {
+    let mut memflow = lock_memflow()?;
+    let section_options = SectionOptions::from(&memflow.config);
+    let target = memflow
+        .target_mut(handle as u32)
+        .ok_or_else(process_not_found)?;
+    Ok((target.boxed_clone(), section_options))
+}
+
+/// Describes each scan result with the symbol its address resolves to.
+///
+/// memflow is only locked for the symbol lookups after the scan finished.
+fn describe_results<T, F: Fn(T, String) -> String>(
+    handle: ProcessHandle,
+    results: Vec<(Address, T)>,
+    describe: F,
+) -> Result<Vec<(Address, String)>> {
+    let memflow = lock_memflow()?;
+    let symbols = symbol_cache(&memflow, handle);
+    Ok(results
+        .into_iter()
+        .map(|(address, result)| {
+            let symbol = symbols
+                .and_then(|symbols| symbols.symbol_by_address(address))
+                .unwrap_or_default();
+            (address, describe(result, symbol))
+        })
+        .collect())
+}
 
 #[no_mangle]
 pub extern "C" fn EnumerateProcesses(callback: EnumerateProcessCallback) {
-    if let Ok(mut memflow) = unsafe { lock_memflow() } {
-        // `fake` ntoskrnl.exe process
-        let mut proc_data = EnumerateProcessData::new(
-            NTOSKRNL_PID,
-            "ntoskrnl.exe",
-            r"\SystemRoot\system32\ntoskrnl.exe",
-        );
+    // refreshing the process list shows the configuration dialog again after it was cancelled
+    retry_init();
+
+    if let Ok(mut memflow) = lock_memflow() {
+        let start = Instant::now();
+
+        // `fake` kernel process
+        let (kernel_name, kernel_path) = memflow.os_kind.kernel_image();
+        let mut proc_data = EnumerateProcessData::new(KERNEL_PID, kernel_name, kernel_path);
         (callback)(&mut proc_data);
 
         // processes
@@ -41,6 +120,8 @@ pub extern "C" fn EnumerateProcesses(callback: EnumerateProcessCallback) {
                 }
             }
         }
+
+        stats::record_enumeration(start.elapsed());
     }
 }
 
@@ -50,113 +131,108 @@ pub extern "C" fn EnumerateRemoteSectionsAndModules(
     callback_section: EnumerateRemoteSectionsCallback,
     callback_module: EnumerateRemoteModulesCallback,
 ) {
-    if let Ok(mut memflow) = unsafe { lock_memflow() } {
+    if let Ok(mut memflow) = lock_memflow() {
+        let start = Instant::now();
         let parse_sections = memflow.config.parse_sections;
 
-        if handle == NTOSKRNL_HANDLE {
-            let kernel = memflow.get_kernel_mut();
-
-            // iterate sections
-            if parse_sections {
-                if let Some(kernel_translate) = kernel.as_mut_impl_virtualtranslate() {
-                    let mut maps = kernel_translate.virt_page_map_vec(mem::gb(1) as imem);
-                    maps.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
-
-                    // TODO: sections need drastic improvement
-                    let mut section_vaddr = 0;
-                    let mut section_size = 0;
-                    for map in maps
-                        .iter()
-                        .filter(|map| map.0.to_umem() < 0xFFFF000000000000u64)
-                    {
-                        if section_vaddr + section_size != map.0.to_umem() {
-                            if section_size > 0 {
-                                let mut section_data = EnumerateRemoteSectionData::new(
-                                    section_vaddr as *mut c_void,
-                                    section_size as usize,
-                                );
-
-                                (callback_section)(&mut section_data);
-                            }
-
-                            section_vaddr = map.0.to_umem();
-                            section_size = map.1;
-                        } else {
-                            section_size += map.1;
-                        }
-                    }
-                }
-            }
+        let module_list = match memflow.modules(handle as u32) {
+            Some(module_list) => module_list,
+            None => return,
+        };
 
-            // iterate modules
-            if let Ok(module_list) = kernel.module_list() {
-                for module in module_list.iter() {
-                    let mut module_data = EnumerateRemoteModuleData::new(
-                        module.base.to_umem() as *mut c_void,
-                        module.size as usize,
-                        &module.path,
-                    );
-                    (callback_module)(&mut module_data);
-                }
-            }
-        } else if let Some(proc) = memflow.get_process_mut(handle as u32) {
-            // iterate sections
-            if parse_sections {
-                if let Some(proc_translate) = proc.as_mut_impl_virtualtranslate() {
-                    let mut maps = proc_translate.virt_page_map_vec(mem::gb(1) as imem);
-                    maps.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
-
-                    // TODO: sections need drastic improvement
-                    let mut section_vaddr = 0;
-                    let mut section_size = 0;
-                    for map in maps
-                        .iter()
-                        .filter(|map| map.0.to_umem() < 0xFFFF000000000000u64)
-                    {
-                        if section_vaddr + section_size != map.0.to_umem() {
-                            if section_size > 0 {
-                                let mut section_data = EnumerateRemoteSectionData::new(
-                                    section_vaddr as *mut c_void,
-                                    section_size as usize,
-                                );
-
-                                (callback_section)(&mut section_data);
-                            }
-
-                            section_vaddr = map.0.to_umem();
-                            section_size = map.1;
-                        } else {
-                            section_size += map.1;
-                        }
-                    }
+        // iterate sections
+        // the sections are enumerated in the background and the latest known sections are reported
+        if parse_sections {
+            if let Some(cached_sections) = memflow.cached_sections(handle as u32) {
+                for section in cached_sections {
+                    (callback_section)(&mut section.to_reclass());
                 }
             }
+        }
 
-            // iterate modules
-            if let Ok(module_list) = proc.module_list() {
-                for module in module_list.iter() {
-                    let mut module_data = EnumerateRemoteModuleData::new(
-                        module.base.to_umem() as *mut c_void,
-                        module.size as usize,
-                        &module.path,
-                    );
-                    (callback_module)(&mut module_data);
-                }
+        // iterate modules
+        if let Ok(module_list) = module_list {
+            for module in module_list.iter() {
+                let mut module_data = EnumerateRemoteModuleData::new(
+                    module.base.to_umem() as *mut c_void,
+                    module.size as usize,
+                    &module.path,
+                );
+                (callback_module)(&mut module_data);
             }
+
+            memflow.update_section_modules(handle as u32, &module_list);
+
+            // PDBs are parsed without holding the lock so reads are not blocked meanwhile
+            drop(memflow);
+            update_symbols(handle as u32, &module_list);
         }
+
+        stats::record_enumeration(start.elapsed());
     }
 }
 
+/// Lists the threads of a process with their id, TEB and user mode stack.
+///
+/// Threads are read from the kernel thread list so the ntoskrnl PDB has to be available.
+/// The kernel itself has no threads to report and threads are not supported on linux.
+#[no_mangle]
+pub extern "C" fn EnumerateRemoteThreads(
+    handle: ProcessHandle,
+    callback: EnumerateRemoteThreadsCallback,
+) {
+    if handle as u32 == KERNEL_HANDLE {
+        return;
+    }
+
+    // resolved before locking memflow as the kernel symbols might have to be loaded first
+    let kernel_offsets = match kernel_offsets() {
+        Some(kernel_offsets) => kernel_offsets,
+        None => return,
+    };
+    let offsets = match &kernel_offsets.threads {
+        Some(offsets) => offsets,
+        None => return,
+    };
+
+    if let Ok(mut memflow) = lock_memflow() {
+        let start = Instant::now();
+        for thread in memflow.process_threads(handle as u32, offsets) {
+            let (stack_limit, stack_base) = thread.stack.unwrap_or_default();
+            let mut thread_data = EnumerateRemoteThreadData::new(
+                thread.id as usize,
+                thread.teb.to_umem() as *mut c_void,
+                stack_base.to_umem() as *mut c_void,
+                stack_limit.to_umem() as *mut c_void,
+            );
+            (callback)(&mut thread_data);
+        }
+        stats::record_enumeration(start.elapsed());
+    }
+}
+
+/// Opens the status window or closes it if it is already open.
+///
+/// The window runs alongside ReClass.NET so this can be bound to a hotkey.
+#[no_mangle]
+pub extern "C" fn ToggleStatusWindow() {
+    toggle_status_window();
+}
+
+/// Opens the window showing the recent log entries.
+///
+/// This function blocks until the window is closed.
+#[no_mangle]
+pub extern "C" fn ShowLogViewer() {
+    gui::log_viewer::show();
+}
+
 #[no_mangle]
 pub extern "C" fn OpenRemoteProcess(id: ProcessId, _desired_access: i32) -> ProcessHandle {
-    if let Ok(mut memflow) = unsafe { lock_memflow() } {
-        if id == NTOSKRNL_PID {
-            NTOSKRNL_HANDLE
-        } else {
-            match memflow.open_process(id as u32) {
-                Ok(handle) => handle as ProcessHandle,
-                Err(_) => ptr::null_mut(),
-            }
+    if let Ok(mut memflow) = lock_memflow() {
+        match memflow.open_process(id as u32) {
+            Ok(handle) => handle as ProcessHandle,
+            Err(_) => ptr::null_mut(),
         }
     } else {
         ptr::null_mut()
@@ -165,14 +241,11 @@ pub extern "C" fn OpenRemoteProcess(id: ProcessId, _desired_access: i32) -> Proc
 
 #[no_mangle]
 pub extern "C" fn IsProcessValid(handle: ProcessHandle) -> bool {
-    if let Ok(mut memflow) = unsafe { lock_memflow() } {
-        if handle == NTOSKRNL_HANDLE {
-            true
-        } else if let Some(process) = memflow.get_process_mut(handle as u32) {
-            process.state() == ProcessState::Alive
-        } else {
-            false
-        }
+    if let Ok(mut memflow) = lock_memflow() {
+        memflow
+            .target_mut(handle as u32)
+            .map(|target| target.is_alive())
+            .unwrap_or_default()
     } else {
         false
     }
@@ -180,10 +253,21 @@ pub extern "C" fn IsProcessValid(handle: ProcessHandle) -> bool {
 
 #[no_mangle]
 pub extern "C" fn CloseRemoteProcess(handle: ProcessHandle) {
-    if let Ok(mut memflow) = unsafe { lock_memflow() } {
-        if handle != NTOSKRNL_HANDLE {
-            memflow.close_process(handle as u32);
-        }
+    if let Ok(mut memflow) = lock_memflow() {
+        memflow.close_process(handle as u32);
+    }
+}
+
+/// Reads memory of a process, the read fails if any part of the range is not readable.
+fn read_memory(
+    memflow: &mut Memflow,
+    handle: ProcessHandle,
+    address: Address,
+    buf: &mut [u8],
+) -> bool {
+    match memflow.target_mut(handle as u32) {
+        Some(mut target) => target.read_raw_into(address, buf).is_ok(),
+        None => false,
     }
 }
 
@@ -195,26 +279,18 @@ pub extern "C" fn ReadRemoteMemory(
     offset: i32,
     size: i32,
 ) -> bool {
-    #[allow(clippy::collapsible_else_if)]
-    if let Ok(mut memflow) = unsafe { lock_memflow() } {
-        if handle == NTOSKRNL_HANDLE {
-            if let Some(mem_view) = memflow.get_kernel_mut().as_mut_impl_memoryview() {
-                let slice = unsafe { slice::from_raw_parts_mut(buffer as *mut u8, size as usize) };
-                mem_view
-                    .read_raw_into((address as u64).wrapping_add(offset as u64).into(), slice)
-                    .is_ok()
-            } else {
-                false
-            }
-        } else {
-            if let Some(proc) = memflow.get_process_mut(handle as u32) {
-                let slice = unsafe { slice::from_raw_parts_mut(buffer as *mut u8, size as usize) };
-                proc.read_raw_into((address as u64).wrapping_add(offset as u64).into(), slice)
-                    .is_ok()
-            } else {
-                false
-            }
-        }
+    if let Ok(mut memflow) = lock_memflow() {
+        let slice = unsafe { slice::from_raw_parts_mut(buffer as *mut u8, size as usize) };
+        // only the reads of ReClass.NET are counted, not the ones of scans and the debugger
+        let start = Instant::now();
+        let success = read_memory(
+            &mut memflow,
+            handle,
+            (address as u64).wrapping_add(offset as u64).into(),
+            slice,
+        );
+        stats::record_read(success, start.elapsed());
+        success
     } else {
         false
     }
@@ -228,58 +304,1078 @@ pub extern "C" fn WriteRemoteMemory(
     offset: i32,
     size: i32,
 ) -> bool {
-    #[allow(clippy::collapsible_else_if)]
-    if let Ok(mut memflow) = unsafe { lock_memflow() } {
-        if handle == NTOSKRNL_HANDLE {
-            if let Some(mem_view) = memflow.get_kernel_mut().as_mut_impl_memoryview() {
+    if let Ok(mut memflow) = lock_memflow() {
+        match memflow.target_mut(handle as u32) {
+            Some(mut target) => {
                 let slice = unsafe { slice::from_raw_parts_mut(buffer as *mut u8, size as usize) };
-                mem_view
+                let success = target
                     .write_raw((address as u64).wrapping_add(offset as u64).into(), slice)
-                    .is_ok()
-            } else {
-                false
+                    .is_ok();
+                stats::record_write(success);
+                success
+            }
+            None => false,
+        }
+    } else {
+        false
+    }
+}
+
+fn symbol_cache(memflow: &Memflow, handle: ProcessHandle) -> Option<&SymbolCache> {
+    memflow.get_symbols(handle as u32)
+}
+
+/// Resolves an address to a null-terminated `module!symbol+offset` string.
+///
+/// Symbols are only available for modules that have already been reported
+/// through `EnumerateRemoteSectionsAndModules`.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn ResolveRemoteSymbol(
+    handle: ProcessHandle,
+    address: *mut c_void,
+    buffer: *mut u16,
+    size: i32,
+) -> bool {
+    if buffer.is_null() || size <= 0 {
+        return false;
+    }
+
+    if let Ok(memflow) = lock_memflow() {
+        if let Some(symbol) = symbol_cache(&memflow, handle)
+            .and_then(|symbols| symbols.symbol_by_address((address as umem).into()))
+        {
+            let slice = unsafe { slice::from_raw_parts_mut(buffer, size as usize) };
+            write_utf16(slice, &symbol)
+        } else {
+            false
+        }
+    } else {
+        false
+    }
+}
+
+/// Resolves a null-terminated `module!symbol+offset` string to an address.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn ResolveRemoteSymbolAddress(
+    handle: ProcessHandle,
+    symbol: *const u16,
+    address: *mut *mut c_void,
+) -> bool {
+    if symbol.is_null() || address.is_null() {
+        return false;
+    }
+
+    if let Ok(memflow) = lock_memflow() {
+        let symbol = unsafe { read_utf16(symbol) };
+        if let Some(resolved) =
+            symbol_cache(&memflow, handle).and_then(|symbols| symbols.address_by_symbol(&symbol))
+        {
+            unsafe { *address = resolved.to_umem() as *mut c_void };
+            true
+        } else {
+            false
+        }
+    } else {
+        false
+    }
+}
+
+/// Enumerates all export and PDB symbols of a module.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn EnumerateRemoteSymbols(
+    handle: ProcessHandle,
+    module: *const u16,
+    callback: EnumerateRemoteSymbolsCallback,
+) -> bool {
+    if module.is_null() {
+        return false;
+    }
+
+    if let Ok(memflow) = lock_memflow() {
+        let module = unsafe { read_utf16(module) };
+        if let Some(module_symbols) =
+            symbol_cache(&memflow, handle).and_then(|symbols| symbols.module_by_name(&module))
+        {
+            for (address, name) in module_symbols.symbols() {
+                let mut symbol_data =
+                    EnumerateRemoteSymbolData::new(address.to_umem() as *mut c_void, name);
+                (callback)(&mut symbol_data);
             }
+            true
         } else {
-            if let Some(proc) = memflow.get_process_mut(handle as u32) {
-                let slice = unsafe { slice::from_raw_parts_mut(buffer as *mut u8, size as usize) };
-                proc.write_raw((address as u64).wrapping_add(offset as u64).into(), slice)
-                    .is_ok()
+            false
+        }
+    } else {
+        false
+    }
+}
+
+/// Returns the size of a type loaded from a PDB in the symbol store or 0 if the type is unknown.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn GetRemoteTypeSize(handle: ProcessHandle, type_name: *const u16) -> usize {
+    if type_name.is_null() {
+        return 0;
+    }
+
+    if let Ok(memflow) = lock_memflow() {
+        let type_name = unsafe { read_utf16(type_name) };
+        symbol_cache(&memflow, handle)
+            .and_then(|symbols| symbols.type_layout(&type_name))
+            .map(|layout| layout.size as usize)
+            .unwrap_or_default()
+    } else {
+        0
+    }
+}
+
+/// Enumerates the fields of a type loaded from a PDB in the symbol store.
+///
+/// The type name can either be a plain name like `_EPROCESS`
+/// or be qualified with a module like `ntoskrnl.exe!_EPROCESS`.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn EnumerateRemoteTypeFields(
+    handle: ProcessHandle,
+    type_name: *const u16,
+    callback: EnumerateRemoteTypeFieldsCallback,
+) -> bool {
+    if type_name.is_null() {
+        return false;
+    }
+
+    if let Ok(memflow) = lock_memflow() {
+        let type_name = unsafe { read_utf16(type_name) };
+        if let Some(layout) =
+            symbol_cache(&memflow, handle).and_then(|symbols| symbols.type_layout(&type_name))
+        {
+            for field in layout.fields.iter() {
+                let bitfield = match field.ty {
+                    FieldType::Bitfield(_, position, length) => Some((position, length)),
+                    _ => None,
+                };
+                let mut field_data = EnumerateRemoteTypeFieldData::new(
+                    field.offset as usize,
+                    field.ty.size() as usize,
+                    bitfield,
+                    &field.name,
+                    &field.ty.name(),
+                );
+                (callback)(&mut field_data);
+            }
+            true
+        } else {
+            false
+        }
+    } else {
+        false
+    }
+}
+
+/// Exports a type loaded from a PDB in the symbol store into a ReClass.NET project.
+///
+/// The project is written as a `.rcnet` file unless the path ends with `.xml`
+/// in which case only the xml class definitions are written.
+/// Types referenced through pointers are exported as classes up to `pointer_depth` levels deep.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn ExportRemoteType(
+    handle: ProcessHandle,
+    type_name: *const u16,
+    path: *const u16,
+    pointer_depth: i32,
+) -> bool {
+    if type_name.is_null() || path.is_null() {
+        return false;
+    }
+
+    if let Ok(mut memflow) = lock_memflow() {
+        let pointer_size = match memflow.target_mut(handle as u32) {
+            Some(target) => target.pointer_size(),
+            None => return false,
+        };
+
+        let type_name = unsafe { read_utf16(type_name) };
+        let path = unsafe { read_utf16(path) };
+        if let Some((database, type_name)) =
+            symbol_cache(&memflow, handle).and_then(|symbols| symbols.type_database(&type_name))
+        {
+            Project::new(
+                database,
+                type_name,
+                pointer_depth.max(0) as u32,
+                pointer_size as u64,
+            )
+            .save(path.as_ref())
+            .is_ok()
+        } else {
+            false
+        }
+    } else {
+        false
+    }
+}
+
+/// Maximum number of results displayed in the scanner windows.
+const MAX_WINDOW_RESULTS: usize = 100_000;
+
+/// Converts the `max_results` of a scan export into a result limit.
+///
+/// All scan exports treat zero or a negative value as "no limit".
+fn result_limit(max_results: i32) -> usize {
+    if max_results > 0 {
+        max_results as usize
+    } else {
+        usize::MAX
+    }
+}
+
+/// Scans the sections of a process (optionally restricted to a module) for a pattern
+/// and resolves each match to a symbol.
+fn scan_pattern(
+    handle: ProcessHandle,
+    pattern: &Pattern,
+    module: &str,
+    executable_only: bool,
+    max_results: usize,
+) -> Result<Vec<(Address, String)>> {
+    let not_found = || Error(ErrorOrigin::Other, ErrorKind::ModuleNotFound);
+
+    let (mut target, section_options) = scan_target(handle)?;
+    let mut target: &mut dyn Target = target.as_mut();
+    let modules = target.modules()?;
+    let module = match module {
+        "" => None,
+        name => Some(scan::find_module(&modules, name).ok_or_else(not_found)?),
+    };
+    let target_sections = sections::enumerate(target, &section_options);
+    let regions = scan::scan_regions(&target_sections, module, executable_only);
+    let results = scan::pattern::scan(&mut target, &regions, pattern, max_results);
+
+    describe_results(
+        handle,
+        results.into_iter().map(|address| (address, ())).collect(),
+        |_, symbol| symbol,
+    )
+}
+
+/// Scans the memory of a process for an IDA-style pattern like `48 8B 05 ? ? ? ? 48 85 C0`.
+///
+/// The scan can be restricted to a single module by passing its name in `module`
+/// and to executable memory by setting `executable_only`.
+/// Each match is reported with the symbol it resolves to.
+/// A `max_results` of zero does not limit the number of results.
+/// Returns the number of matches or -1 if the scan failed.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn ScanRemotePattern(
+    handle: ProcessHandle,
+    pattern: *const u16,
+    module: *const u16,
+    executable_only: bool,
+    max_results: i32,
+    callback: EnumerateRemoteSymbolsCallback,
+) -> i32 {
+    if pattern.is_null() {
+        return -1;
+    }
+
+    let pattern = match Pattern::parse(&unsafe { read_utf16(pattern) }) {
+        Ok(pattern) => pattern,
+        Err(_) => return -1,
+    };
+    let module = unsafe { read_utf16(module) };
+
+    match scan_pattern(
+        handle,
+        &pattern,
+        module.trim(),
+        executable_only,
+        result_limit(max_results),
+    ) {
+        Ok(results) => {
+            for (address, symbol) in results.iter() {
+                let mut result_data =
+                    EnumerateRemoteSymbolData::new(address.to_umem() as *mut c_void, symbol);
+                (callback)(&mut result_data);
+            }
+            results.len() as i32
+        }
+        Err(_) => -1,
+    }
+}
+
+/// Opens the pattern scanner window for a process.
+///
+/// This function blocks until the window is closed.
+#[no_mangle]
+pub extern "C" fn ShowPatternScanner(handle: ProcessHandle) {
+    gui::pattern_scan::show(|pattern, module, executable_only| {
+        let pattern = Pattern::parse(pattern)?;
+        scan_pattern(
+            handle,
+            &pattern,
+            module,
+            executable_only,
+            MAX_WINDOW_RESULTS,
+        )
+    });
+}
+
+/// Searches the sections of a process for pointer chains from a module to `target`.
+fn scan_pointers(
+    handle: ProcessHandle,
+    ptr_target: Address,
+    options: PointerScanOptions,
+) -> Result<Vec<PointerChain>> {
+    let (mut target, section_options) = scan_target(handle)?;
+    let mut target: &mut dyn Target = target.as_mut();
+    let pointer_size = target.pointer_size();
+    let modules = target.modules()?;
+    let regions = sections::regions(target, &section_options);
+    scan::pointer::scan(
+        &mut target,
+        &regions,
+        &modules,
+        ptr_target,
+        pointer_size,
+        options,
+    )
+}
+
+/// Filters pointer chains down to the ones which still resolve to `target`.
+fn rescan_pointers(
+    handle: ProcessHandle,
+    ptr_target: Address,
+    chains: &[PointerChain],
+) -> Result<Vec<PointerChain>> {
+    let (mut target, _) = scan_target(handle)?;
+    let mut target: &mut dyn Target = target.as_mut();
+    let pointer_size = target.pointer_size();
+    let modules = target.modules()?;
+    Ok(scan::pointer::rescan(
+        &mut target,
+        &modules,
+        chains,
+        ptr_target,
+        pointer_size,
+    ))
+}
+
+/// Searches the memory of a process for pointer chains leading from a module to `address`.
+///
+/// Chains are at most `max_depth` pointers long and each dereferenced pointer
+/// may be followed by an offset of up to `max_offset` bytes.
+/// The results are written to the text file at `path`, one chain per line.
+/// A `max_results` of zero does not limit the number of results.
+/// Returns the number of chains found or -1 if the scan failed.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn ScanRemotePointers(
+    handle: ProcessHandle,
+    address: *mut c_void,
+    max_depth: i32,
+    max_offset: i32,
+    max_results: i32,
+    path: *const u16,
+) -> i32 {
+    if path.is_null() || max_depth <= 0 || max_offset < 0 {
+        return -1;
+    }
+
+    let path = unsafe { read_utf16(path) };
+    let options = PointerScanOptions {
+        max_depth: max_depth as usize,
+        max_offset: max_offset as umem,
+        max_results: result_limit(max_results),
+    };
+    match scan_pointers(handle, (address as umem).into(), options)
+        .and_then(|chains| scan::pointer::save(path.as_ref(), &chains).map(|_| chains))
+    {
+        Ok(chains) => chains.len() as i32,
+        Err(_) => -1,
+    }
+}
+
+/// Loads the pointer chains from `path` and writes the ones which still resolve to `address` to `output_path`.
+///
+/// This allows re-validating the results of a previous scan after the target has been restarted.
+/// Returns the number of remaining chains or -1 if the rescan failed.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn RescanRemotePointers(
+    handle: ProcessHandle,
+    address: *mut c_void,
+    path: *const u16,
+    output_path: *const u16,
+) -> i32 {
+    if path.is_null() || output_path.is_null() {
+        return -1;
+    }
+
+    let path = unsafe { read_utf16(path) };
+    let output_path = unsafe { read_utf16(output_path) };
+    match scan::pointer::load(path.as_ref())
+        .and_then(|chains| rescan_pointers(handle, (address as umem).into(), &chains))
+        .and_then(|chains| scan::pointer::save(output_path.as_ref(), &chains).map(|_| chains))
+    {
+        Ok(chains) => chains.len() as i32,
+        Err(_) => -1,
+    }
+}
+
+/// Opens the pointer scanner window for a process.
+///
+/// This function blocks until the window is closed.
+#[no_mangle]
+pub extern "C" fn ShowPointerScanner(handle: ProcessHandle) {
+    gui::pointer_scan::show(
+        MAX_WINDOW_RESULTS,
+        |target, options| scan_pointers(handle, target, options),
+        |target, chains| rescan_pointers(handle, target, chains),
+    );
+}
+
+/// Starts a new value scan over the sections of a process and stores it for the handle.
+///
+/// Returns the number of results.
+fn first_value_scan(
+    handle: ProcessHandle,
+    value_type: ValueType,
+    condition: &ValueCondition,
+) -> Result<usize> {
+    let (mut target, section_options) = scan_target(handle)?;
+    let mut target: &mut dyn Target = target.as_mut();
+    let regions = sections::regions(target, &section_options);
+    let scan = ValueScan::first(&mut target, &regions, value_type, condition)?;
+
+    let len = scan.len();
+    store_value_scan(handle, scan)?;
+    Ok(len)
+}
+
+/// Refines the value scan of a handle and returns the number of remaining results.
+fn next_value_scan(
+    handle: ProcessHandle,
+    compare: CompareType,
+    value: &str,
+    value2: &str,
+) -> Result<usize> {
+    let (mut scan, mut target) = {
+        let mut memflow = lock_memflow()?;
+        let target = memflow
+            .target_mut(handle as u32)
+            .ok_or_else(process_not_found)?
+            .boxed_clone();
+        let scan = memflow.take_value_scan(handle as u32).ok_or_else(|| {
+            Error(ErrorOrigin::Other, ErrorKind::NotFound).log_warn("no value scan in progress")
+        })?;
+        (scan, target)
+    };
+
+    let result =
+        ValueCondition::parse(scan.value_type(), compare, value, value2).and_then(|condition| {
+            let mut target: &mut dyn Target = target.as_mut();
+            scan.next(&mut target, &condition)
+        });
+
+    // a failed refinement keeps the previous results
+    let len = scan.len();
+    store_value_scan(handle, scan)?;
+    result.map(|_| len)
+}
+
+/// Stores the value scan of a handle unless the handle has been closed during the scan.
+fn store_value_scan(handle: ProcessHandle, scan: ValueScan) -> Result<()> {
+    let mut memflow = lock_memflow()?;
+    memflow
+        .target_mut(handle as u32)
+        .ok_or_else(process_not_found)?;
+    memflow.set_value_scan(handle as u32, scan);
+    Ok(())
+}
+
+fn value_scan_results(
+    memflow: &Memflow,
+    handle: ProcessHandle,
+    max_results: usize,
+) -> Vec<(Address, String)> {
+    memflow
+        .get_value_scan(handle as u32)
+        .map(|scan| scan.results(max_results))
+        .unwrap_or_default()
+}
+
+/// Starts a new value scan over the memory of a process.
+///
+/// `value_type` is one of Int8 (0), Int16 (1), Int32 (2), Int64 (3), Float (4), Double (5),
+/// String (6) or a byte array (7) in the form of an IDA-style pattern.
+/// The first scan only supports the comparisons exact (0) and range (1)
+/// where `value2` contains the upper bound of the range.
+/// Returns the number of results or -1 if the scan failed.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn FirstScanRemoteValue(
+    handle: ProcessHandle,
+    value_type: i32,
+    compare: i32,
+    value: *const u16,
+    value2: *const u16,
+) -> i32 {
+    let (value_type, compare) = match (
+        ValueType::from_index(value_type as usize),
+        CompareType::from_index(compare as usize),
+    ) {
+        (Some(value_type), Some(compare)) => (value_type, compare),
+        _ => return -1,
+    };
+
+    let value = unsafe { read_utf16(value) };
+    let value2 = unsafe { read_utf16(value2) };
+    match ValueCondition::parse(value_type, compare, &value, &value2)
+        .and_then(|condition| first_value_scan(handle, value_type, &condition))
+    {
+        Ok(len) => len as i32,
+        Err(_) => -1,
+    }
+}
+
+/// Refines the results of the current value scan.
+///
+/// `compare` is one of exact (0), range (1), changed (2), unchanged (3), increased (4) or decreased (5).
+/// Results which could not be read or do not match the comparison are removed.
+/// Returns the number of remaining results or -1 if the scan failed.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn NextScanRemoteValue(
+    handle: ProcessHandle,
+    compare: i32,
+    value: *const u16,
+    value2: *const u16,
+) -> i32 {
+    let compare = match CompareType::from_index(compare as usize) {
+        Some(compare) => compare,
+        None => return -1,
+    };
+
+    let value = unsafe { read_utf16(value) };
+    let value2 = unsafe { read_utf16(value2) };
+    match next_value_scan(handle, compare, &value, &value2) {
+        Ok(len) => len as i32,
+        Err(_) => -1,
+    }
+}
+
+/// Reports up to `max_results` results of the current value scan with their value as of the last scan.
+///
+/// A `max_results` of zero does not limit the number of results.
+#[no_mangle]
+pub extern "C" fn EnumerateRemoteValueScanResults(
+    handle: ProcessHandle,
+    max_results: i32,
+    callback: EnumerateRemoteSymbolsCallback,
+) {
+    if let Ok(memflow) = lock_memflow() {
+        for (address, value) in value_scan_results(&memflow, handle, result_limit(max_results)) {
+            let mut result_data =
+                EnumerateRemoteSymbolData::new(address.to_umem() as *mut c_void, &value);
+            (callback)(&mut result_data);
+        }
+    }
+}
+
+/// Discards the results of the current value scan.
+#[no_mangle]
+pub extern "C" fn ResetRemoteValueScan(handle: ProcessHandle) {
+    if let Ok(mut memflow) = lock_memflow() {
+        memflow.take_value_scan(handle as u32);
+    }
+}
+
+/// Opens the value scanner window for a process.
+///
+/// The window shares its scan state with the value scan exports.
+/// This function blocks until the window is closed.
+#[no_mangle]
+pub extern "C" fn ShowValueScanner(handle: ProcessHandle) {
+    gui::value_scan::show(
+        |value_type, compare, value, value2| {
+            let condition = ValueCondition::parse(value_type, compare, value, value2)?;
+            let len = first_value_scan(handle, value_type, &condition)?;
+            let memflow = lock_memflow()?;
+            Ok((
+                len,
+                value_scan_results(&memflow, handle, MAX_WINDOW_RESULTS),
+            ))
+        },
+        |compare, value, value2| {
+            let len = next_value_scan(handle, compare, value, value2)?;
+            let memflow = lock_memflow()?;
+            Ok((
+                len,
+                value_scan_results(&memflow, handle, MAX_WINDOW_RESULTS),
+            ))
+        },
+        || {
+            if let Ok(mut memflow) = lock_memflow() {
+                memflow.take_value_scan(handle as u32);
+            }
+        },
+    );
+}
+
+/// Searches the sections of a process for a string in the given encodings.
+fn find_strings(
+    handle: ProcessHandle,
+    text: &str,
+    encodings: &[StringEncoding],
+    max_results: usize,
+) -> Result<Vec<(Address, String)>> {
+    let (mut target, section_options) = scan_target(handle)?;
+    let mut target: &mut dyn Target = target.as_mut();
+    let regions = sections::regions(target, &section_options);
+    let results = scan::string::find(&mut target, &regions, text, encodings, max_results)?;
+
+    describe_results(handle, results, |encoding, symbol| {
+        format!("{} {}", encoding.name(), symbol)
+            .trim_end()
+            .to_string()
+    })
+}
+
+/// Searches the sections of a process for pointers to `target` (or up to `max_offset` bytes before it).
+fn find_references(
+    handle: ProcessHandle,
+    ptr_target: Address,
+    max_offset: umem,
+    max_results: usize,
+) -> Result<Vec<(Address, String)>> {
+    let (mut target, section_options) = scan_target(handle)?;
+    let mut target: &mut dyn Target = target.as_mut();
+    let pointer_size = target.pointer_size();
+    let regions = sections::regions(target, &section_options);
+    let results = scan::pointer::find_references(
+        &mut target,
+        &regions,
+        ptr_target,
+        max_offset,
+        pointer_size,
+        max_results,
+    );
+
+    describe_results(handle, results, |offset, symbol| {
+        format!("+{:#x} {}", offset, symbol).trim_end().to_string()
+    })
+}
+
+/// Searches the sections of a process for an ASCII and/or UTF-16 string.
+///
+/// The search fails if `ascii` is set and the string contains non-ASCII characters.
+/// Each match is reported with its encoding and the symbol it resolves to.
+/// A `max_results` of zero does not limit the number of results.
+/// Returns the number of matches or -1 if the search failed.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn FindRemoteStrings(
+    handle: ProcessHandle,
+    text: *const u16,
+    ascii: bool,
+    utf16: bool,
+    max_results: i32,
+    callback: EnumerateRemoteSymbolsCallback,
+) -> i32 {
+    if text.is_null() {
+        return -1;
+    }
+
+    let text = unsafe { read_utf16(text) };
+    let encodings = string_encodings(ascii, utf16);
+    match find_strings(handle, &text, &encodings, result_limit(max_results)) {
+        Ok(results) => {
+            for (address, description) in results.iter() {
+                let mut result_data =
+                    EnumerateRemoteSymbolData::new(address.to_umem() as *mut c_void, description);
+                (callback)(&mut result_data);
+            }
+            results.len() as i32
+        }
+        Err(_) => -1,
+    }
+}
+
+/// Searches the sections of a process for pointers to `address`.
+///
+/// Pointers up to `max_offset` bytes before the address are reported as well
+/// which allows finding references to the start of an object containing the address.
+/// Each pointer is reported with its offset to the address and the symbol it resolves to.
+/// A `max_results` of zero does not limit the number of results.
+/// Returns the number of pointers found or -1 if the search failed.
+#[no_mangle]
+pub extern "C" fn FindRemoteReferences(
+    handle: ProcessHandle,
+    address: *mut c_void,
+    max_offset: i32,
+    max_results: i32,
+    callback: EnumerateRemoteSymbolsCallback,
+) -> i32 {
+    match find_references(
+        handle,
+        (address as umem).into(),
+        max_offset.max(0) as umem,
+        result_limit(max_results),
+    ) {
+        Ok(results) => {
+            for (address, description) in results.iter() {
+                let mut result_data =
+                    EnumerateRemoteSymbolData::new(address.to_umem() as *mut c_void, description);
+                (callback)(&mut result_data);
+            }
+            results.len() as i32
+        }
+        Err(_) => -1,
+    }
+}
+
+fn string_encodings(ascii: bool, utf16: bool) -> Vec<StringEncoding> {
+    let mut encodings = Vec::new();
+    if ascii {
+        encodings.push(StringEncoding::Ascii);
+    }
+    if utf16 {
+        encodings.push(StringEncoding::Utf16);
+    }
+    encodings
+}
+
+/// Opens the string search and cross-reference window for a process.
+///
+/// This function blocks until the window is closed.
+#[no_mangle]
+pub extern "C" fn ShowStringSearch(handle: ProcessHandle) {
+    gui::string_search::show(
+        |text, ascii, utf16| {
+            find_strings(
+                handle,
+                text,
+                &string_encodings(ascii, utf16),
+                MAX_WINDOW_RESULTS,
+            )
+        },
+        |target, max_offset| find_references(handle, target, max_offset, MAX_WINDOW_RESULTS),
+    );
+}
+
+/// Resolves the class name of a vtable or of an object whose first member is a vtable.
+fn resolve_class_name(
+    memflow: &mut Memflow,
+    handle: ProcessHandle,
+    address: Address,
+) -> Option<String> {
+    fn resolve<T: MemoryView>(
+        mem: &mut T,
+        address: Address,
+        pointer_size: usize,
+    ) -> Option<String> {
+        rtti::class_name(mem, address, pointer_size).or_else(|| {
+            let vtable = if pointer_size == 4 {
+                mem.read_addr32(address)
             } else {
-                false
+                mem.read_addr64(address)
             }
+            .ok()?;
+            rtti::class_name(mem, vtable, pointer_size)
+        })
+    }
+
+    let mut target = memflow.target_mut(handle as u32)?;
+    let pointer_size = target.pointer_size();
+    resolve(&mut target, address, pointer_size)
+}
+
+/// Lists all vtables with RTTI in a module of a process.
+fn module_classes(handle: ProcessHandle, module: &str) -> Result<Vec<(Address, String)>> {
+    let not_found = || Error(ErrorOrigin::Other, ErrorKind::ModuleNotFound);
+
+    // the module image is read on a clone so memflow is not locked meanwhile
+    let (mut target, _) = scan_target(handle)?;
+    let mut target: &mut dyn Target = target.as_mut();
+    let pointer_size = target.pointer_size();
+    let modules = target.modules()?;
+    let module = scan::find_module(&modules, module).ok_or_else(not_found)?;
+    Ok(rtti::module_classes(&mut target, module, pointer_size))
+}
+
+/// Resolves a vtable (or an object starting with a vtable pointer) to a null-terminated class name
+/// using the MSVC run-time type information of the class.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn ResolveRemoteClassName(
+    handle: ProcessHandle,
+    address: *mut c_void,
+    buffer: *mut u16,
+    size: i32,
+) -> bool {
+    if buffer.is_null() || size <= 0 {
+        return false;
+    }
+
+    if let Ok(mut memflow) = lock_memflow() {
+        if let Some(name) = resolve_class_name(&mut memflow, handle, (address as umem).into()) {
+            let slice = unsafe { slice::from_raw_parts_mut(buffer, size as usize) };
+            write_utf16(slice, &name)
+        } else {
+            false
         }
     } else {
         false
     }
 }
 
+/// Reports all vtables with run-time type information in a module together with their class name.
+///
+/// Returns the number of vtables found or -1 if the module could not be found.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[no_mangle]
-pub extern "C" fn ControlRemoteProcess(_handle: ProcessHandle, _action: i32) {}
+pub extern "C" fn EnumerateRemoteClasses(
+    handle: ProcessHandle,
+    module: *const u16,
+    callback: EnumerateRemoteSymbolsCallback,
+) -> i32 {
+    if module.is_null() {
+        return -1;
+    }
+
+    let module = unsafe { read_utf16(module) };
+    match module_classes(handle, module.trim()) {
+        Ok(classes) => {
+            for (vtable, name) in classes.iter() {
+                let mut class_data =
+                    EnumerateRemoteSymbolData::new(vtable.to_umem() as *mut c_void, name);
+                (callback)(&mut class_data);
+            }
+            classes.len() as i32
+        }
+        Err(_) => -1,
+    }
+}
 
+/// Opens the window listing all classes with run-time type information in a module.
+///
+/// This function blocks until the window is closed.
 #[no_mangle]
-pub extern "C" fn AttachDebuggerToProcess(_id: ProcessId) -> bool {
-    false
+pub extern "C" fn ShowClassList(handle: ProcessHandle) {
+    gui::class_list::show(|module| module_classes(handle, module));
 }
 
+/// Opens the snapshot diff window for a region of a process.
+///
+/// Snapshots are read through the same path as `ReadRemoteMemory`.
+/// This function blocks until the window is closed.
 #[no_mangle]
-pub extern "C" fn DetachDebuggerFromProcess(_id: ProcessId) {}
+pub extern "C" fn ShowSnapshotDiff(handle: ProcessHandle, address: *mut c_void, size: i32) {
+    gui::snapshot_diff::show(
+        (address as umem).into(),
+        size.max(0) as umem,
+        |base, size| {
+            let mut memflow = lock_memflow()?;
+            Snapshot::take(base, size, |address, buf| {
+                read_memory(&mut memflow, handle, address, buf)
+            })
+        },
+    );
+}
+
+/// Validates a process control request.
+///
+/// memflow only has access to the memory of the target. Suspending a thread requires the
+/// target kernel to queue the scheduler APC of the thread and resuming it requires signaling
+/// the suspend event of the thread, which wakes up its waiters. Neither can be emulated by
+/// writing to the kernel thread objects without racing the dispatcher of the running system,
+/// so every action is rejected as unsupported.
+fn control_process(
+    memflow: &mut Memflow,
+    handle: ProcessHandle,
+    action: ControlRemoteProcessAction,
+) -> Result<()> {
+    memflow
+        .target_mut(handle as u32)
+        .ok_or_else(process_not_found)?;
+
+    Err(
+        Error(ErrorOrigin::OsLayer, ErrorKind::UnsupportedOptionalFeature).log_warn(format!(
+            "unable to {} process: process control is not supported by memflow",
+            action.name()
+        )),
+    )
+}
 
+/// Set once the user has been told that process control is not supported.
+static CONTROL_UNSUPPORTED_SHOWN: AtomicBool = AtomicBool::new(false);
+
+/// Suspends (0), resumes (1) or terminates (2) a process.
+///
+/// None of the actions is supported by memflow, see `control_process`.
+/// The user is notified about this once instead of the request being silently ignored.
+#[no_mangle]
+pub extern "C" fn ControlRemoteProcess(handle: ProcessHandle, action: i32) {
+    let action = match ControlRemoteProcessAction::from_i32(action) {
+        Some(action) => action,
+        None => return,
+    };
+
+    // failures to initialize memflow are reported by `lock_memflow` and a cancelled
+    // configuration dialog is not an error, neither is reported as unsupported action
+    let result = match lock_memflow() {
+        Ok(mut memflow) => control_process(&mut memflow, handle, action),
+        Err(_) => return,
+    };
+
+    if let Err(err) = result {
+        if !CONTROL_UNSUPPORTED_SHOWN.swap(true, Ordering::Relaxed) {
+            // the lock is released before showing the blocking alert
+            gui::alert::show_error(
+                "Process Control",
+                &format!("Unable to {} the process", action.name()),
+                err,
+            );
+        }
+    }
+}
+
+/// Starts emulating a debugger for the process by polling watched addresses.
+///
+/// memflow does not interact with the target, so only write breakpoints set through
+/// `SetHardwareBreakpoint` are supported. Writes are detected by reading the watched
+/// addresses in the configured `watch_interval` and comparing them against their previous value.
+/// This means that only writes which change the value are reported and the writing
+/// thread, instruction address and registers of the generated debug events are always empty.
+/// The addresses are read on a clone of the process so polling does not block ReClass.NET.
+#[no_mangle]
+pub extern "C" fn AttachDebuggerToProcess(id: ProcessId) -> bool {
+    if let Ok(mut memflow) = lock_memflow() {
+        let interval = Duration::from_millis(memflow.config.watch_interval.max(1));
+        let mut target = match memflow.target_mut(id as u32) {
+            Some(target) => target.boxed_clone(),
+            None => return false,
+        };
+
+        let watch = WriteWatch::new(id as u32);
+        let poller = watch.poller();
+        memflow.write_watch = Some(watch);
+
+        std::thread::spawn(move || {
+            while poller.running() {
+                let mut mem: &mut dyn Target = target.as_mut();
+                poller.poll(|address, buf| mem.read_raw_into(address, buf).is_ok());
+                std::thread::sleep(interval);
+            }
+        });
+
+        true
+    } else {
+        false
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn DetachDebuggerFromProcess(id: ProcessId) {
+    if let Ok(mut memflow) = lock_memflow() {
+        if let Some(watch) = &memflow.write_watch {
+            if watch.process_id() == id as u32 {
+                memflow.write_watch = None;
+            }
+        }
+    }
+}
+
+/// Waits up to `timeout` milliseconds for a watched address to be written.
+///
+/// The reported event is a single step exception caused by the breakpoint register of the watch.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[no_mangle]
-pub extern "C" fn AwaitDebugEvent(_event: *mut c_void /*DebugEvent*/, _timeout: i32) -> bool {
-    false
+pub extern "C" fn AwaitDebugEvent(event: *mut DebugEvent, timeout: i32) -> bool {
+    if event.is_null() {
+        return false;
+    }
+
+    // the memflow lock must not be held while waiting as the polling thread requires it
+    let (process_id, events) = match lock_memflow() {
+        Ok(memflow) => match &memflow.write_watch {
+            Some(watch) => (watch.process_id(), watch.events()),
+            None => return false,
+        },
+        Err(_) => return false,
+    };
+
+    match events.wait(Duration::from_millis(timeout.max(0) as u64)) {
+        Some(watch_event) => {
+            unsafe {
+                *event = DebugEvent::breakpoint(process_id as ProcessId, watch_event.register)
+            };
+            true
+        }
+        None => false,
+    }
 }
 
+/// Polling continues regardless of the continue status so there is nothing to handle.
 #[no_mangle]
-pub extern "C" fn HandleDebugEvent(_event: *mut c_void /*DebugEvent*/) {}
+pub extern "C" fn HandleDebugEvent(_event: *mut DebugEvent) {}
 
+/// Sets or removes a write breakpoint on `size` bytes at `address`.
+///
+/// Only write breakpoints (`ty` = 2) can be emulated, execute and access breakpoints are rejected.
+/// `reg` is the breakpoint register to use, `Dr0` = 1 to `Dr3` = 4.
 #[no_mangle]
 pub extern "C" fn SetHardwareBreakpoint(
-    _id: ProcessId,
-    _address: *mut c_void,
-    _reg: i32,
-    _ty: i32,
-    _size: i32,
-    _set: bool,
+    id: ProcessId,
+    address: *mut c_void,
+    reg: i32,
+    ty: i32,
+    size: i32,
+    set: bool,
 ) -> bool {
-    false
+    if let Ok(mut memflow) = lock_memflow() {
+        let watch = match memflow.write_watch.take() {
+            Some(watch) if watch.process_id() == id as u32 => watch,
+            watch => {
+                memflow.write_watch = watch;
+                return false;
+            }
+        };
+
+        let result = if !set {
+            watch.clear(reg)
+        } else if ty == HARDWARE_BREAKPOINT_WRITE {
+            watch.set(
+                reg,
+                (address as umem).into(),
+                size as usize,
+                |address, buf| read_memory(&mut memflow, id as ProcessHandle, address, buf),
+            )
+        } else {
+            false
+        };
+
+        memflow.write_watch = Some(watch);
+        result
+    } else {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn result_limit_zero_is_unlimited() {
+        assert_eq!(result_limit(0), usize::MAX);
+        assert_eq!(result_limit(-1), usize::MAX);
+        assert_eq!(result_limit(1), 1);
+        assert_eq!(result_limit(i32::MAX), i32::MAX as usize);
+    }
 }
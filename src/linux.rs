@@ -0,0 +1,18 @@
+use memflow::prelude::v1::*;
+
+/// Name of the kernel pseudo-process of Linux targets.
+pub const KERNEL_NAME: &str = "vmlinux";
+/// Path of the kernel pseudo-process of Linux targets.
+pub const KERNEL_PATH: &str = "/boot/vmlinux";
+
+/// Magic bytes at the start of every ELF file.
+const ELF_MAGIC: [u8; 4] = *b"\x7fELF";
+
+/// Checks whether a module is a mapped ELF object.
+///
+/// Linux OS plugins report every file backed mapping as a module,
+/// including data files like fonts or locales which are not of interest.
+pub fn is_elf<T: MemoryView>(mem: &mut T, module: &ModuleInfo) -> bool {
+    let mut magic = [0u8; 4];
+    mem.read_raw_into(module.base, &mut magic).is_ok() && magic == ELF_MAGIC
+}
@@ -0,0 +1,314 @@
+use crate::gui::Config;
+
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Number of recent entries kept in memory for the log viewer.
+const MAX_RECENT: usize = 2000;
+
+/// Log file which is used on Windows if no path is configured.
+#[cfg(not(unix))]
+const DEFAULT_LOG_PATH: &str = "memflow_reclass.log";
+
+/// The source of a log entry the level can be configured for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Source {
+    /// This plugin.
+    Plugin,
+    /// The memflow crate itself.
+    Memflow,
+    /// Connectors and OS plugins loaded by memflow.
+    Connector,
+}
+
+impl Source {
+    /// Attributes a log target to its source by the crate name.
+    ///
+    /// Connectors and OS plugins are named `memflow_<name>` (e.g. `memflow_qemu` or `memflow_win32`),
+    /// all other crates are dependencies of this plugin like `pdb` or `winit`.
+    fn from_target(target: &str) -> Self {
+        let crate_name = target.split("::").next().unwrap_or_default();
+        match crate_name {
+            "memflow_reclass" => Source::Plugin,
+            "memflow" => Source::Memflow,
+            _ if crate_name.starts_with("memflow_") => Source::Connector,
+            _ => Source::Plugin,
+        }
+    }
+}
+
+/// Where log entries are written to.
+enum Sink {
+    Stdout,
+    File {
+        path: PathBuf,
+        file: File,
+        size: u64,
+        max_size: u64,
+        max_files: u32,
+    },
+}
+
+impl Sink {
+    /// Appends to the log file at `path`, the file is not rotated until limits are set.
+    fn file(path: PathBuf) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Sink::File {
+            path,
+            file,
+            size,
+            max_size: 0,
+            max_files: 0,
+        })
+    }
+
+    fn write(&mut self, line: &str) {
+        match self {
+            Sink::Stdout => {
+                println!("{}", line);
+            }
+            Sink::File {
+                path,
+                file,
+                size,
+                max_size,
+                max_files,
+            } => {
+                if *max_size != 0 && *size + line.len() as u64 > *max_size {
+                    match rotate(path, *max_files) {
+                        Ok(new_file) => {
+                            *file = new_file;
+                            *size = 0;
+                        }
+                        Err(err) => {
+                            // keep appending instead of failing again for every entry
+                            eprintln!("unable to rotate log file, rotation is disabled: {}", err);
+                            *max_size = 0;
+                        }
+                    }
+                }
+                if writeln!(file, "{}", line).is_ok() {
+                    *size += line.len() as u64 + 1;
+                }
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        match self {
+            Sink::Stdout => io::stdout().flush().ok(),
+            Sink::File { file, .. } => file.flush().ok(),
+        };
+    }
+}
+
+/// Moves `path` to `path.1`, `path.1` to `path.2` and so on, the oldest file is deleted.
+///
+/// Returns the new empty log file at `path`.
+fn rotate(path: &Path, max_files: u32) -> io::Result<File> {
+    let rotated = |idx: u32| {
+        let mut name = path.as_os_str().to_owned();
+        name.push(format!(".{}", idx));
+        PathBuf::from(name)
+    };
+
+    if max_files == 0 {
+        return File::create(path);
+    }
+    fs::remove_file(rotated(max_files)).ok();
+    for idx in (1..max_files).rev() {
+        fs::rename(rotated(idx), rotated(idx + 1)).ok();
+    }
+    fs::rename(path, rotated(1))?;
+    File::create(path)
+}
+
+struct State {
+    sink: Option<Sink>,
+    /// The configured log file, empty for the default destination.
+    path: String,
+    plugin: LevelFilter,
+    memflow: LevelFilter,
+    connector: LevelFilter,
+    recent: VecDeque<String>,
+}
+
+impl State {
+    fn level(&self, source: Source) -> LevelFilter {
+        match source {
+            Source::Plugin => self.plugin,
+            Source::Memflow => self.memflow,
+            Source::Connector => self.connector,
+        }
+    }
+}
+
+/// Writes log entries to the configured sink and keeps the recent entries for the log viewer.
+struct Logger {
+    state: Mutex<State>,
+}
+
+static LOGGER: Logger = Logger {
+    state: Mutex::new(State {
+        sink: None,
+        path: String::new(),
+        plugin: LevelFilter::Info,
+        memflow: LevelFilter::Info,
+        connector: LevelFilter::Info,
+        recent: VecDeque::new(),
+    }),
+};
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.state
+            .lock()
+            .map(|state| metadata.level() <= state.level(Source::from_target(metadata.target())))
+            .unwrap_or_default()
+    }
+
+    fn log(&self, record: &Record) {
+        if let Ok(mut state) = self.state.lock() {
+            if record.level() > state.level(Source::from_target(record.target())) {
+                return;
+            }
+
+            let line = format!(
+                "[{}] {} - {}",
+                record.level(),
+                record.target(),
+                record.args()
+            );
+            if let Some(sink) = &mut state.sink {
+                sink.write(&line);
+            }
+            if state.recent.len() >= MAX_RECENT {
+                state.recent.pop_front();
+            }
+            state.recent.push_back(line);
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            if let Some(sink) = &mut state.sink {
+                sink.flush();
+            }
+        }
+    }
+}
+
+/// Installs the logger with its default destination, this may be called repeatedly.
+///
+/// Logs are written to stdout on unix and to `memflow_reclass.log` on other platforms
+/// until a config is applied with `configure`.
+pub fn init() {
+    if let Ok(mut state) = LOGGER.state.lock() {
+        if state.sink.is_none() {
+            state.sink = default_sink();
+        }
+    }
+    // the only possible error is that the logger has been set before
+    log::set_logger(&LOGGER).ok();
+    update_max_level();
+}
+
+/// Applies the log destination, rotation and levels of a config.
+///
+/// The log file is only reopened if its path changed.
+pub fn configure(config: &Config) {
+    if let Ok(mut state) = LOGGER.state.lock() {
+        if state.sink.is_none() || state.path != config.log_path {
+            state.sink = if config.log_path.is_empty() {
+                default_sink()
+            } else {
+                match Sink::file(PathBuf::from(&config.log_path)) {
+                    Ok(sink) => Some(sink),
+                    Err(err) => {
+                        eprintln!("unable to open log file {}: {}", config.log_path, err);
+                        default_sink()
+                    }
+                }
+            };
+            state.path = config.log_path.clone();
+        }
+        if let Some(Sink::File {
+            max_size,
+            max_files,
+            ..
+        }) = &mut state.sink
+        {
+            // the default log file is not rotated
+            if !config.log_path.is_empty() {
+                *max_size = config.log_max_size;
+                *max_files = config.log_max_files;
+            }
+        }
+
+        let plugin = parse_level(&config.log_level).unwrap_or(LevelFilter::Off);
+        state.plugin = plugin;
+        state.memflow = parse_level(&config.log_level_memflow).unwrap_or(plugin);
+        state.connector = parse_level(&config.log_level_connector).unwrap_or(plugin);
+    }
+    update_max_level();
+}
+
+/// Returns the most recent log entries, the oldest entry first.
+pub fn recent() -> Vec<String> {
+    LOGGER
+        .state
+        .lock()
+        .map(|state| state.recent.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Removes all entries from the log viewer, the log file is not touched.
+pub fn clear_recent() {
+    if let Ok(mut state) = LOGGER.state.lock() {
+        state.recent.clear();
+    }
+}
+
+/// Parses a log level, `None` is returned for an empty or unknown level.
+pub fn parse_level(level: &str) -> Option<LevelFilter> {
+    match level.to_lowercase().as_ref() {
+        "off" => Some(LevelFilter::Off),
+        "error" => Some(LevelFilter::Error),
+        "warn" => Some(LevelFilter::Warn),
+        "info" => Some(LevelFilter::Info),
+        "debug" => Some(LevelFilter::Debug),
+        "trace" => Some(LevelFilter::Trace),
+        _ => None,
+    }
+}
+
+/// Lets the `log` macros skip entries which none of the sources would accept.
+fn update_max_level() {
+    if let Ok(state) = LOGGER.state.lock() {
+        log::set_max_level(state.plugin.max(state.memflow).max(state.connector));
+    }
+}
+
+#[cfg(unix)]
+fn default_sink() -> Option<Sink> {
+    Some(Sink::Stdout)
+}
+
+/// The default log file is truncated every time it is opened.
+#[cfg(not(unix))]
+fn default_sink() -> Option<Sink> {
+    let file = File::create(DEFAULT_LOG_PATH).ok()?;
+    Some(Sink::File {
+        path: PathBuf::from(DEFAULT_LOG_PATH),
+        file,
+        size: 0,
+        max_size: 0,
+        max_files: 0,
+    })
+}
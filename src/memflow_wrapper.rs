@@ -1,65 +1,381 @@
-use crate::gui::{
-    alert,
-    settings::{Config, Settings},
-};
+use crate::debugger::WriteWatch;
+use crate::gui::status::{self, StatusInfo};
+use crate::gui::{alert, Config, Settings};
+use crate::heaps::{self, HeapOffsets, HeapRegion};
+use crate::kernel::{self, SystemVaOffsets};
+use crate::linux;
+use crate::logging;
+use crate::reclass::{SectionCategory, SectionProtection, SectionType};
+use crate::scan::value::ValueScan;
+use crate::section_cache::{SectionCache, SectionWorker};
+use crate::sections::{self, Section, SectionOptions};
+use crate::stats;
+use crate::symbols::{store::SymbolStore, ModuleSymbols, SymbolCache};
+use crate::threads::{self, ThreadInfo, ThreadOffsets};
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex, MutexGuard};
-
-use log::LevelFilter;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
 
 use memflow::prelude::v1::*;
 
-static mut MEMFLOW_INSTANCE: Option<Arc<Mutex<Memflow>>> = None;
+static MEMFLOW_INSTANCE: OnceLock<Mutex<Memflow>> = OnceLock::new();
+/// Held while memflow is initialized so the configuration dialog is only shown once.
+static INIT_LOCK: Mutex<()> = Mutex::new(());
 
-pub unsafe fn lock_memflow<'a>() -> Result<MutexGuard<'a, Memflow>> {
-    if MEMFLOW_INSTANCE.is_none() {
-        match Memflow::try_init() {
-            Ok(memflow) => {
-                MEMFLOW_INSTANCE = Some(Arc::new(Mutex::new(memflow)));
-            }
-            Err(err) => {
-                alert::show_error(
-                    "Unable to load memflow",
-                    "Memflow failed to initialize some of its components",
-                    err,
-                );
-                return Err(err.log_error("unable to initialize memflow"));
-            }
-        };
+/// Set when the user cancelled the configuration dialog.
+///
+/// Memflow is not initialized again until `retry_init` is called,
+/// otherwise every export would show the dialog again.
+static INIT_CANCELLED: AtomicBool = AtomicBool::new(false);
+
+/// The error returned by `lock_memflow` after the configuration dialog has been cancelled.
+pub fn init_cancelled() -> Error {
+    Error(ErrorOrigin::Other, ErrorKind::Uninitialized)
+}
+
+/// Shows the configuration dialog again on the next `lock_memflow` if it has been cancelled.
+pub fn retry_init() {
+    INIT_CANCELLED.store(false, Ordering::Relaxed);
+}
+
+/// Locks memflow, it is initialized on first use.
+///
+/// Concurrent calls wait for the initialization instead of showing the configuration dialog again.
+pub fn lock_memflow() -> Result<MutexGuard<'static, Memflow>> {
+    let memflow = match MEMFLOW_INSTANCE.get() {
+        Some(memflow) => memflow,
+        None => init_memflow()?,
+    };
+    memflow.lock().map_err(|_| {
+        Error(ErrorOrigin::Other, ErrorKind::NotFound).log_error("unable to lock memflow")
+    })
+}
+
+/// Locks memflow if it has been initialized already, the configuration dialog is never shown.
+pub fn lock_memflow_initialized() -> Option<MutexGuard<'static, Memflow>> {
+    MEMFLOW_INSTANCE.get()?.lock().ok()
+}
+
+fn init_memflow() -> Result<&'static Mutex<Memflow>> {
+    let _init = INIT_LOCK.lock().map_err(|_| {
+        Error(ErrorOrigin::Other, ErrorKind::NotFound).log_error("unable to lock memflow")
+    })?;
+    // another thread might have initialized memflow while waiting for the lock
+    if let Some(memflow) = MEMFLOW_INSTANCE.get() {
+        return Ok(memflow);
+    }
+    if INIT_CANCELLED.load(Ordering::Relaxed) {
+        return Err(init_cancelled());
     }
 
-    if let Some(memflow) = MEMFLOW_INSTANCE.as_ref() {
-        if let Ok(memflow) = memflow.lock() {
+    match Memflow::try_init() {
+        Ok(memflow) => {
+            let show_status = memflow.config.show_status;
+            let memflow = MEMFLOW_INSTANCE.get_or_init(|| Mutex::new(memflow));
+            if show_status {
+                show_status_window();
+            }
             Ok(memflow)
-        } else {
-            Err(Error(ErrorOrigin::Other, ErrorKind::NotFound).log_error("unable to lock memflow"))
         }
-    } else {
-        Err(Error(ErrorOrigin::Other, ErrorKind::NotFound)
-            .log_error("memflow is not properly initialized"))
+        Err(err) if INIT_CANCELLED.load(Ordering::Relaxed) => {
+            log::info!("memflow initialization has been cancelled");
+            Err(err)
+        }
+        Err(err) => {
+            alert::show_error(
+                "Unable to load memflow",
+                "Memflow failed to initialize some of its components",
+                err,
+            );
+            Err(err.log_error("unable to initialize memflow"))
+        }
+    }
+}
+
+/// Set while the status window is open.
+static STATUS_WINDOW: AtomicBool = AtomicBool::new(false);
+
+/// Opens the status window on a separate thread if it is not open yet.
+pub fn show_status_window() {
+    if STATUS_WINDOW.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    std::thread::spawn(|| {
+        status::show(&STATUS_WINDOW, || {
+            let memflow = lock_memflow_initialized()?;
+            let mut handles = memflow.handles.keys().copied().collect::<Vec<_>>();
+            handles.push(KERNEL_HANDLE);
+            handles.sort_unstable();
+            Some(StatusInfo {
+                connector: memflow.config.connector.clone(),
+                os: format!(
+                    "{} ({:?}, {:?})",
+                    memflow.config.os,
+                    memflow.os_kind,
+                    memflow.os.info().arch
+                ),
+                handles,
+            })
+        })
+    });
+}
+
+/// Closes the status window if it is open, otherwise it is opened.
+pub fn toggle_status_window() {
+    if !STATUS_WINDOW.swap(false, Ordering::Relaxed) {
+        show_status_window();
+    }
+}
+
+/// Handle of the kernel pseudo-process, the pid of the `System` process.
+///
+/// On Linux pid 4 always belongs to a kernel thread which has no user mode
+/// memory, so the handle does not collide with a process that could be opened.
+pub const KERNEL_HANDLE: u32 = 4;
+
+/// The kind of operating system the OS plugin introspects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OsKind {
+    Windows,
+    Linux,
+}
+
+impl OsKind {
+    /// Guesses the operating system from the name of the OS plugin.
+    ///
+    /// The `native` plugin introspects the host, unknown plugins are treated as Windows.
+    pub fn from_os_name(name: &str) -> Self {
+        match name {
+            "native" if cfg!(windows) => OsKind::Windows,
+            "native" => OsKind::Linux,
+            name if name.contains("linux") => OsKind::Linux,
+            _ => OsKind::Windows,
+        }
+    }
+
+    /// Name and path of the kernel pseudo-process.
+    pub fn kernel_image(self) -> (&'static str, &'static str) {
+        match self {
+            OsKind::Windows => ("ntoskrnl.exe", r"\SystemRoot\system32\ntoskrnl.exe"),
+            OsKind::Linux => (linux::KERNEL_NAME, linux::KERNEL_PATH),
+        }
+    }
+}
+
+/// An address space the exports operate on, either the kernel or an opened process.
+///
+/// All exports access targets through this trait so the kernel and processes
+/// share a single code path. Memory access of a `&mut dyn Target` is available
+/// through the memflow `MemoryView` trait.
+pub trait Target: Send {
+    fn read_raw_ops(&mut self, data: ReadRawMemOps) -> Result<()>;
+    fn write_raw_ops(&mut self, data: WriteRawMemOps) -> Result<()>;
+    fn view_metadata(&self) -> MemoryViewMetadata;
+    /// Lists the mapped memory between `start` and `end` together with its page type.
+    ///
+    /// Ranges which are less than `gap` bytes apart are merged.
+    fn mapped_ranges(&mut self, gap: imem, start: Address, end: Address) -> Vec<MemoryRange>;
+
+    fn modules(&mut self) -> Result<Vec<ModuleInfo>>;
+    fn module_exports(&mut self, module: &ModuleInfo) -> Result<Vec<ExportInfo>>;
+    fn module_sections(&mut self, module: &ModuleInfo) -> Result<Vec<SectionInfo>>;
+
+    /// Size of a pointer in the target, this is 4 bytes for wow64 processes.
+    fn pointer_size(&self) -> usize;
+    /// Whether the target covers the kernel half of the address space.
+    fn is_kernel(&self) -> bool;
+    fn is_alive(&mut self) -> bool;
+    /// Clones the target so it can be accessed without holding the memflow lock.
+    fn boxed_clone(&self) -> Box<dyn Target>;
+}
+
+impl Target for OsInstanceArcBox<'static> {
+    fn read_raw_ops(&mut self, data: ReadRawMemOps) -> Result<()> {
+        self.as_mut_impl_memoryview()
+            .ok_or(Error(
+                ErrorOrigin::Other,
+                ErrorKind::UnsupportedOptionalFeature,
+            ))?
+            .read_raw_iter(data)
+    }
+
+    fn write_raw_ops(&mut self, data: WriteRawMemOps) -> Result<()> {
+        self.as_mut_impl_memoryview()
+            .ok_or(Error(
+                ErrorOrigin::Other,
+                ErrorKind::UnsupportedOptionalFeature,
+            ))?
+            .write_raw_iter(data)
+    }
+
+    fn view_metadata(&self) -> MemoryViewMetadata {
+        // the kernel memory view is only reachable mutably
+        MemoryViewMetadata {
+            max_address: Address::invalid(),
+            real_size: 0,
+            readonly: false,
+            little_endian: true,
+            arch_bits: self.info().arch.into_obj().bits(),
+        }
+    }
+
+    fn mapped_ranges(&mut self, gap: imem, start: Address, end: Address) -> Vec<MemoryRange> {
+        self.as_mut_impl_virtualtranslate()
+            .map(|translate| translate.virt_page_map_range_vec(gap, start, end))
+            .unwrap_or_default()
+    }
+
+    fn modules(&mut self) -> Result<Vec<ModuleInfo>> {
+        Os::module_list(self)
+    }
+
+    fn module_exports(&mut self, module: &ModuleInfo) -> Result<Vec<ExportInfo>> {
+        Os::module_export_list(self, module)
+    }
+
+    fn module_sections(&mut self, module: &ModuleInfo) -> Result<Vec<SectionInfo>> {
+        Os::module_section_list(self, module)
+    }
+
+    fn pointer_size(&self) -> usize {
+        self.info().arch.into_obj().size_addr()
+    }
+
+    fn is_kernel(&self) -> bool {
+        true
+    }
+
+    fn is_alive(&mut self) -> bool {
+        true
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Target> {
+        Box::new(self.clone())
+    }
+}
+
+impl Target for IntoProcessInstanceArcBox<'static> {
+    fn read_raw_ops(&mut self, data: ReadRawMemOps) -> Result<()> {
+        MemoryView::read_raw_iter(self, data)
+    }
+
+    fn write_raw_ops(&mut self, data: WriteRawMemOps) -> Result<()> {
+        MemoryView::write_raw_iter(self, data)
+    }
+
+    fn view_metadata(&self) -> MemoryViewMetadata {
+        MemoryView::metadata(self)
+    }
+
+    fn mapped_ranges(&mut self, gap: imem, start: Address, end: Address) -> Vec<MemoryRange> {
+        // processes of OS plugins without page table access (e.g. `native`) report their mappings
+        Process::mapped_mem_range_vec(self, gap, start, end)
+    }
+
+    fn modules(&mut self) -> Result<Vec<ModuleInfo>> {
+        Process::module_list(self)
+    }
+
+    fn module_exports(&mut self, module: &ModuleInfo) -> Result<Vec<ExportInfo>> {
+        Process::module_export_list(self, module)
+    }
+
+    fn module_sections(&mut self, module: &ModuleInfo) -> Result<Vec<SectionInfo>> {
+        Process::module_section_list(self, module)
+    }
+
+    fn pointer_size(&self) -> usize {
+        self.info().proc_arch.into_obj().size_addr()
+    }
+
+    fn is_kernel(&self) -> bool {
+        false
+    }
+
+    fn is_alive(&mut self) -> bool {
+        self.state() == ProcessState::Alive
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Target> {
+        Box::new(self.clone())
+    }
+}
+
+impl MemoryView for &mut dyn Target {
+    fn read_raw_iter(&mut self, data: ReadRawMemOps) -> Result<()> {
+        (**self).read_raw_ops(data)
+    }
+
+    fn write_raw_iter(&mut self, data: WriteRawMemOps) -> Result<()> {
+        (**self).write_raw_ops(data)
+    }
+
+    fn metadata(&self) -> MemoryViewMetadata {
+        (**self).view_metadata()
+    }
+}
+
+/// Interval in which kernel structure offsets which could not be resolved are retried.
+const KERNEL_OFFSETS_RETRY: Duration = Duration::from_secs(30);
+
+/// Offsets into the Windows kernel structures taken from the ntoskrnl PDB.
+pub struct KernelOffsets {
+    pub threads: Option<ThreadOffsets>,
+    pub heaps: Option<HeapOffsets>,
+}
+
+impl KernelOffsets {
+    /// Resolves all offsets and warns about the features which are unavailable without them.
+    ///
+    /// The warnings are only logged on the first attempt.
+    fn resolve(symbols: Option<&SymbolCache>, first_attempt: bool) -> Self {
+        let threads = symbols.and_then(ThreadOffsets::new);
+        if threads.is_none() && first_attempt {
+            log::warn!("unable to enumerate threads: ntoskrnl symbols are not available");
+        }
+        let heaps = symbols.and_then(HeapOffsets::new);
+        if heaps.is_none() && first_attempt {
+            log::warn!("unable to enumerate heaps: ntoskrnl symbols are not available");
+        }
+        Self { threads, heaps }
+    }
+
+    fn complete(&self) -> bool {
+        self.threads.is_some() && self.heaps.is_some()
     }
 }
 
 pub struct Memflow {
     pub config: Config,
     pub os: OsInstanceArcBox<'static>,
+    pub os_kind: OsKind,
     pub handles: HashMap<u32, IntoProcessInstanceArcBox<'static>>,
+    /// Shared with the threads loading symbols without holding the memflow lock.
+    pub symbol_store: Arc<Mutex<SymbolStore>>,
+    /// Symbols of the kernel (`KERNEL_HANDLE`) and all opened processes.
+    pub symbols: HashMap<u32, SymbolCache>,
+    pub value_scans: HashMap<u32, ValueScan>,
+    pub write_watch: Option<WriteWatch>,
+    pub section_caches: HashMap<u32, SectionCache>,
+    /// Resolved on first use by `kernel_offsets` together with the time of the attempt.
+    pub kernel_offsets: Option<(Arc<KernelOffsets>, Instant)>,
 }
 
 impl Memflow {
     pub fn try_init() -> Result<Self> {
         // setup logging
-        #[cfg(unix)]
-        simple_logging::log_to(std::io::stdout(), LevelFilter::Info);
-        #[cfg(not(unix))]
-        simple_logging::log_to_file("memflow_reclass.log", LevelFilter::Info).ok();
+        logging::init();
 
-        // load config file and set initial logging level
+        // load config file and apply the initial logging config
         let mut settings = Settings::new();
-        log_level_from_str(settings.config().log_level.as_ref());
+        logging::configure(&settings.config());
 
         // show configuration dialog
-        settings.configure();
+        if !settings.configure() {
+            INIT_CANCELLED.store(true, Ordering::Relaxed);
+            return Err(init_cancelled());
+        }
         if let Err(err) = settings.persist() {
             alert::show_error(
                 "Unable to save settings",
@@ -69,62 +385,545 @@ impl Memflow {
         }
         let config = settings.config();
 
-        // update logging level after showing the configuration dialog
-        log_level_from_str(config.log_level.as_ref());
+        // update logging after showing the configuration dialog
+        logging::configure(&config);
 
         // load connector
-        let inventory = Inventory::scan();
-        let os = {
-            match inventory
-                .builder()
-                .connector(&config.connector)
-                .args(config.args.parse()?)
-                .os("win32")
-                .build()
-            {
-                Ok(os) => os,
-                Err(err) => {
-                    return Err(err);
-                }
-            }
-        };
+        let os = build_os(&config)?;
+        let os_kind = OsKind::from_os_name(&config.os);
+
+        let symbol_store = Arc::new(Mutex::new(SymbolStore::new(&config.symbol_store)));
 
         Ok(Self {
             config,
             os,
+            os_kind,
             handles: HashMap::new(),
+            symbol_store,
+            symbols: HashMap::new(),
+            value_scans: HashMap::new(),
+            write_watch: None,
+            section_caches: HashMap::new(),
+            kernel_offsets: None,
         })
     }
 
+    /// Opens a process and returns its handle, the `System` process is opened as the kernel.
     pub fn open_process(&mut self, pid: u32) -> Result<u32> {
+        if pid == KERNEL_HANDLE {
+            return Ok(KERNEL_HANDLE);
+        }
         let proc = self.os.clone().into_process_by_pid(pid)?;
         self.handles.insert(pid, proc);
         Ok(pid)
     }
 
+    /// Closes a handle and discards all state associated with it.
+    ///
+    /// The kernel is never closed and keeps its symbols.
     pub fn close_process(&mut self, handle: u32) {
-        self.handles.remove(&handle);
+        if handle != KERNEL_HANDLE {
+            self.handles.remove(&handle);
+            self.symbols.remove(&handle);
+        }
+        self.value_scans.remove(&handle);
+        self.section_caches.remove(&handle);
+        if let Some(watch) = &self.write_watch {
+            if watch.process_id() == handle {
+                self.write_watch = None;
+            }
+        }
     }
 
-    pub fn get_kernel_mut(&mut self) -> &mut OsInstanceArcBox<'static> {
-        &mut self.os
+    /// Returns the kernel or the opened process of a handle.
+    pub fn target_mut(&mut self, handle: u32) -> Option<&mut dyn Target> {
+        target_mut(&mut self.os, &mut self.handles, handle)
     }
 
-    pub fn get_process_mut(
-        &mut self,
-        handle: u32,
-    ) -> Option<&mut IntoProcessInstanceArcBox<'static>> {
-        self.handles.get_mut(&handle)
+    /// Lists the modules of a handle, returns `None` if the handle is not open.
+    pub fn modules(&mut self, handle: u32) -> Option<Result<Vec<ModuleInfo>>> {
+        let os_kind = self.os_kind;
+        self.target_mut(handle)
+            .map(|target| list_modules(target, os_kind))
+    }
+
+    pub fn get_symbols(&self, handle: u32) -> Option<&SymbolCache> {
+        self.symbols.get(&handle)
+    }
+
+    /// Returns the latest known sections of a handle.
+    ///
+    /// The sections are enumerated by a background worker which is started on first use
+    /// and restarted if it stopped, so `None` is returned until its first run finished.
+    pub fn cached_sections(&mut self, handle: u32) -> Option<Vec<Section>> {
+        if let Some(cache) = self.section_caches.get(&handle) {
+            if !cache.stopped() {
+                let sections = cache.sections();
+                stats::record_section_cache_lookup(sections.is_some());
+                return sections;
+            }
+            log::debug!("section worker of handle {} stopped, restarting it", handle);
+        }
+        stats::record_section_cache_lookup(false);
+
+        let cache = SectionCache::new();
+        spawn_section_worker(handle, cache.worker());
+        self.section_caches.insert(handle, cache);
+        None
+    }
+
+    /// Refreshes the cached sections of a handle if its module list changed.
+    pub fn update_section_modules(&mut self, handle: u32, modules: &[ModuleInfo]) {
+        if let Some(cache) = self.section_caches.get_mut(&handle) {
+            cache.update_modules(modules);
+        }
+    }
+
+    /// Lists the threads of a process together with their TEB and stack.
+    ///
+    /// The offsets are returned by `kernel_offsets` which has to be called without holding the lock.
+    pub fn process_threads(&mut self, handle: u32, offsets: &ThreadOffsets) -> Vec<ThreadInfo> {
+        match self.handles.get_mut(&handle) {
+            Some(proc) => read_threads(&mut self.os, proc, offsets),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn get_value_scan(&self, handle: u32) -> Option<&ValueScan> {
+        self.value_scans.get(&handle)
+    }
+
+    /// Removes the value scan of a handle so it can be refined while the process is borrowed.
+    pub fn take_value_scan(&mut self, handle: u32) -> Option<ValueScan> {
+        self.value_scans.remove(&handle)
+    }
+
+    pub fn set_value_scan(&mut self, handle: u32, scan: ValueScan) {
+        self.value_scans.insert(handle, scan);
+    }
+}
+
+/// Builds the connector and OS plugin chain of a config.
+pub fn build_os(config: &Config) -> Result<OsInstanceArcBox<'static>> {
+    let inventory = Inventory::scan();
+    // OS plugins like `native` do not require a connector
+    let builder = if config.connector.is_empty() {
+        inventory.builder().os(&config.os)
+    } else {
+        inventory
+            .builder()
+            .connector(&config.connector)
+            .args(config.args.parse()?)
+            .os(&config.os)
+    };
+    builder.build()
+}
+
+/// Summary of a successfully built connector and OS plugin.
+pub struct ConnectionInfo {
+    pub kernel_version: Option<String>,
+    pub process_count: usize,
+    pub memory_size: Option<umem>,
+}
+
+/// Builds the connector and OS plugin of a config and reads some basic information
+/// to verify that the target is accessible.
+pub fn test_connection(config: &Config) -> Result<ConnectionInfo> {
+    let mut os = build_os(config)?;
+    let process_count = os.process_address_list()?.len();
+    let memory_size = os
+        .as_mut_impl_physicalmemory()
+        .map(|phys_mem| phys_mem.metadata().real_size)
+        .filter(|size| *size != 0);
+    let kernel_version = match OsKind::from_os_name(&config.os) {
+        OsKind::Windows => kernel::windows_version(&mut os),
+        OsKind::Linux => None,
+    };
+    Ok(ConnectionInfo {
+        kernel_version,
+        process_count,
+        memory_size,
+    })
+}
+
+/// Loads the exports and PDBs of all modules of a handle which are not cached yet.
+///
+/// memflow is only locked to look up the missing modules and to store their symbols,
+/// the exports and PDBs are loaded on a clone of the target so reads are not blocked.
+pub fn update_symbols(handle: u32, modules: &[ModuleInfo]) {
+    let (mut target, store, missing) = match lock_memflow() {
+        Ok(mut memflow) => {
+            let missing = memflow.symbols.entry(handle).or_default().missing(modules);
+            if missing.is_empty() {
+                return;
+            }
+            let store = memflow.symbol_store.clone();
+            match memflow.target_mut(handle) {
+                Some(target) => (target.boxed_clone(), store, missing),
+                None => return,
+            }
+        }
+        Err(_) => return,
+    };
+
+    let loaded = load_symbols(target.as_mut(), &store, &missing);
+
+    if let Ok(mut memflow) = lock_memflow() {
+        // the handle might have been closed in the meantime
+        if memflow.target_mut(handle).is_some() {
+            memflow.symbols.entry(handle).or_default().insert(loaded);
+        }
+    }
+}
+
+/// Reads the exports of `modules` and loads their PDBs from the symbol store.
+fn load_symbols(
+    mut target: &mut dyn Target,
+    store: &Mutex<SymbolStore>,
+    modules: &[ModuleInfo],
+) -> Vec<ModuleSymbols> {
+    modules
+        .iter()
+        .map(|module| {
+            let exports = target.module_exports(module).unwrap_or_else(|err| {
+                log::debug!("unable to parse exports of {}: {}", module.name, err);
+                Vec::new()
+            });
+            let database = store
+                .lock()
+                .ok()
+                .and_then(|mut store| store.load_module(&mut target, module));
+            ModuleSymbols::new(module, exports, database)
+        })
+        .collect()
+}
+
+/// Returns the Windows kernel structure offsets, `None` if the OS is not Windows.
+///
+/// The kernel symbols are loaded on first use without holding the memflow lock.
+/// Offsets which could not be resolved are retried in the `KERNEL_OFFSETS_RETRY` interval,
+/// so a ntoskrnl PDB which is added to the symbol store later is still picked up.
+pub fn kernel_offsets() -> Option<Arc<KernelOffsets>> {
+    let mut kernel = {
+        let mut memflow = lock_memflow().ok()?;
+        if memflow.os_kind != OsKind::Windows {
+            return None;
+        }
+        match &memflow.kernel_offsets {
+            Some((offsets, resolved))
+                if offsets.complete() || resolved.elapsed() < KERNEL_OFFSETS_RETRY =>
+            {
+                return Some(offsets.clone())
+            }
+            // reload the kernel image in case its PDB has been added to the store meanwhile
+            Some(_) => {
+                if let Some(symbols) = memflow.symbols.get_mut(&KERNEL_HANDLE) {
+                    symbols.evict_without_database("ntoskrnl");
+                }
+            }
+            None => {}
+        }
+        memflow.os.clone()
+    };
+    if let Ok(module_list) = kernel.modules() {
+        update_symbols(KERNEL_HANDLE, &module_list);
+    }
+
+    let mut memflow = lock_memflow().ok()?;
+    // another thread might have resolved the offsets in the meantime
+    let retry = match &memflow.kernel_offsets {
+        Some((offsets, resolved)) => {
+            !offsets.complete() && resolved.elapsed() >= KERNEL_OFFSETS_RETRY
+        }
+        None => true,
+    };
+    if retry {
+        let first_attempt = memflow.kernel_offsets.is_none();
+        let offsets = KernelOffsets::resolve(memflow.get_symbols(KERNEL_HANDLE), first_attempt);
+        memflow.kernel_offsets = Some((Arc::new(offsets), Instant::now()));
+    }
+    memflow
+        .kernel_offsets
+        .as_ref()
+        .map(|(offsets, _)| offsets.clone())
+}
+
+/// Lists the modules of a target as they are reported to ReClass.NET.
+///
+/// Linux OS plugins report every file backed mapping as a module,
+/// only the mapped ELF objects are kept.
+pub fn list_modules(mut target: &mut dyn Target, os_kind: OsKind) -> Result<Vec<ModuleInfo>> {
+    let mut modules = target.modules()?;
+    if os_kind == OsKind::Linux {
+        modules.retain(|module| linux::is_elf(&mut target, module));
+    }
+    Ok(modules)
+}
+
+/// Reads the threads of a process from its kernel thread list.
+fn read_threads(
+    kernel: &mut OsInstanceArcBox<'static>,
+    proc: &mut IntoProcessInstanceArcBox<'static>,
+    offsets: &ThreadOffsets,
+) -> Vec<ThreadInfo> {
+    let info = proc.info().clone();
+    let kernel_arch = kernel.info().arch.into_obj();
+    let ids = match kernel.as_mut_impl_memoryview() {
+        Some(kernel) => threads::enumerate(kernel, kernel_arch, offsets, info.address),
+        None => return Vec::new(),
+    };
+
+    // the TEB referenced by the kernel is always the native one, also for wow64 processes
+    let arch = info.sys_arch.into_obj();
+    ids.into_iter()
+        .map(|(id, teb)| threads::thread_info(proc, arch, offsets, id, teb))
+        .collect()
+}
+
+/// Reads the segments of all heaps of a process.
+///
+/// The PEB is located through the `_EPROCESS` of the process.
+fn read_heaps(
+    kernel: &mut OsInstanceArcBox<'static>,
+    proc: &mut IntoProcessInstanceArcBox<'static>,
+    offsets: &HeapOffsets,
+) -> Vec<HeapRegion> {
+    let info = proc.info().clone();
+    let kernel_arch = kernel.info().arch.into_obj();
+    let peb = match kernel.as_mut_impl_memoryview().map(|kernel| {
+        kernel
+            .read_addr_arch(kernel_arch, info.address + offsets.peb())
+            .data_part()
+    }) {
+        Some(Ok(peb)) if !peb.is_null() => peb,
+        _ => return Vec::new(),
+    };
+
+    heaps::enumerate(proc, info.sys_arch.into_obj(), offsets, peb)
+}
+
+fn target_mut<'a>(
+    os: &'a mut OsInstanceArcBox<'static>,
+    handles: &'a mut HashMap<u32, IntoProcessInstanceArcBox<'static>>,
+    handle: u32,
+) -> Option<&'a mut dyn Target> {
+    if handle == KERNEL_HANDLE {
+        Some(os)
+    } else {
+        handles.get_mut(&handle).map(|proc| proc as &mut dyn Target)
     }
 }
 
-fn log_level_from_str(log_level: &str) {
-    match log_level.to_lowercase().as_ref() {
-        "error" => log::set_max_level(LevelFilter::Error),
-        "warn" => log::set_max_level(LevelFilter::Warn),
-        "info" => log::set_max_level(LevelFilter::Info),
-        "debug" => log::set_max_level(LevelFilter::Debug),
-        "trace" => log::set_max_level(LevelFilter::Trace),
-        _ => log::set_max_level(LevelFilter::Off),
+/// Clones of the kernel and process of a handle which its sections are enumerated
+/// and labeled with, so memflow does not have to be locked while the target is read.
+struct SectionLabeler {
+    os_kind: OsKind,
+    kernel: OsInstanceArcBox<'static>,
+    /// The opened process, `None` for the kernel.
+    process: Option<IntoProcessInstanceArcBox<'static>>,
+    /// Requires the ntoskrnl PDB to be loaded already.
+    system_va_offsets: Option<SystemVaOffsets>,
+}
+
+impl SectionLabeler {
+    /// Clones the targets of a handle, returns `None` if the handle is not open.
+    fn new(memflow: &Memflow, handle: u32) -> Option<Self> {
+        let process = if handle == KERNEL_HANDLE {
+            None
+        } else {
+            Some(memflow.handles.get(&handle)?.clone())
+        };
+        Some(Self {
+            os_kind: memflow.os_kind,
+            kernel: memflow.os.clone(),
+            process,
+            system_va_offsets: memflow
+                .get_symbols(KERNEL_HANDLE)
+                .and_then(SystemVaOffsets::new),
+        })
+    }
+
+    /// Returns the kernel or the process of the handle.
+    fn target(&mut self) -> &mut dyn Target {
+        match &mut self.process {
+            Some(proc) => proc,
+            None => &mut self.kernel,
+        }
+    }
+
+    /// Labels the sections with the regions known for the kind of target
+    /// and attributes the sections inside of modules to their module.
+    ///
+    /// `module_list` is the unfiltered module list of the target, on Linux the mapped files
+    /// which are not reported as modules still name the sections they are mapped to.
+    fn label(&mut self, target_sections: &mut Vec<Section>, module_list: &[ModuleInfo]) {
+        let (modules, mappings): (Vec<_>, Vec<_>) = if self.os_kind == OsKind::Linux {
+            let mut target = self.target();
+            module_list
+                .iter()
+                .cloned()
+                .partition(|module| linux::is_elf(&mut target, module))
+        } else {
+            (module_list.to_vec(), Vec::new())
+        };
+
+        // the known regions are read from Windows kernel structures
+        if self.os_kind == OsKind::Windows {
+            if self.process.is_some() {
+                self.label_process_sections(target_sections);
+            } else {
+                self.label_kernel_sections(target_sections, &modules);
+            }
+        }
+        sections::label_mappings(target_sections, &mappings);
+        sections::label_modules(target_sections, &modules);
+    }
+
+    /// Labels the sections of the kernel address space.
+    ///
+    /// The mapped kernel memory is labeled with the system regions it belongs to
+    /// and the PE sections of all loaded drivers are added on top.
+    fn label_kernel_sections(
+        &mut self,
+        kernel_sections: &mut Vec<Section>,
+        modules: &[ModuleInfo],
+    ) {
+        let arch = self.kernel.info().arch.into_obj();
+        match (
+            &self.system_va_offsets,
+            self.kernel.as_mut_impl_memoryview(),
+        ) {
+            (Some(offsets), Some(kernel)) => {
+                for region in kernel::system_regions(kernel, arch, offsets) {
+                    sections::label(
+                        kernel_sections,
+                        &Section::named(
+                            region.base,
+                            region.size,
+                            SectionType::Private,
+                            region.category,
+                            region.name,
+                        ),
+                    );
+                }
+            }
+            _ => log::debug!("unable to read system regions: ntoskrnl symbols are not available"),
+        }
+
+        for module in modules.iter() {
+            let module_sections = match self.kernel.module_sections(module) {
+                Ok(module_sections) => module_sections,
+                Err(err) => {
+                    log::debug!("unable to parse sections of {}: {}", module.name, err);
+                    continue;
+                }
+            };
+            for section in module_sections {
+                let (category, protection) = if kernel::is_code_section(&section.name) {
+                    (SectionCategory::Code, SectionProtection::READ_EXECUTE)
+                } else {
+                    (SectionCategory::Data, SectionProtection::READ_WRITE)
+                };
+                let mut driver_section = Section::named(
+                    section.base,
+                    section.size,
+                    SectionType::Image,
+                    category,
+                    section.name.to_string(),
+                );
+                driver_section.protection = protection;
+                sections::insert(kernel_sections, driver_section);
+            }
+        }
     }
+
+    /// Labels the stacks, TEBs and heaps of the process in its sections.
+    fn label_process_sections(&mut self, process_sections: &mut Vec<Section>) {
+        let (proc, offsets) = match (&mut self.process, kernel_offsets()) {
+            (Some(proc), Some(offsets)) => (proc, offsets),
+            _ => return,
+        };
+
+        let threads = match &offsets.threads {
+            Some(offsets) => read_threads(&mut self.kernel, proc, offsets),
+            None => Vec::new(),
+        };
+        for thread in threads {
+            if let Some((limit, base)) = thread.stack {
+                sections::insert(
+                    process_sections,
+                    Section::named(
+                        limit,
+                        (base - limit) as umem,
+                        SectionType::Private,
+                        SectionCategory::Data,
+                        format!("Stack {}", thread.id),
+                    ),
+                );
+            }
+            sections::insert(
+                process_sections,
+                Section::named(
+                    thread.teb,
+                    thread.teb_size,
+                    SectionType::Private,
+                    SectionCategory::Data,
+                    format!("TEB {}", thread.id),
+                ),
+            );
+        }
+
+        let heaps = match &offsets.heaps {
+            Some(offsets) => read_heaps(&mut self.kernel, proc, offsets),
+            None => Vec::new(),
+        };
+        for heap in heaps {
+            sections::insert(
+                process_sections,
+                Section::named(
+                    heap.base,
+                    heap.size,
+                    SectionType::Private,
+                    SectionCategory::Heap,
+                    format!("Heap {}", heap.index),
+                ),
+            );
+        }
+    }
+}
+
+/// Periodically enumerates the sections of a handle in the background.
+///
+/// The page tables are walked and the sections are labeled on clones of the targets
+/// so reads of ReClass.NET are not blocked, memflow is only locked to clone them.
+fn spawn_section_worker(handle: u32, worker: SectionWorker) {
+    std::thread::spawn(move || {
+        while worker.running() {
+            let (mut labeler, options, refresh) = match lock_memflow() {
+                Ok(memflow) => {
+                    let options = SectionOptions::from(&memflow.config);
+                    let refresh = Duration::from_millis(memflow.config.section_refresh);
+                    match SectionLabeler::new(&memflow, handle) {
+                        Some(labeler) => (labeler, options, refresh),
+                        None => break,
+                    }
+                }
+                Err(_) => break,
+            };
+
+            let mut target_sections = sections::enumerate(labeler.target(), &options);
+            let module_list = labeler.target().modules().unwrap_or_default();
+            labeler.label(&mut target_sections, &module_list);
+
+            // the handle might have been closed in the meantime
+            if !worker.running() {
+                break;
+            }
+            worker.store(target_sections);
+
+            if !worker.wait(refresh) {
+                break;
+            }
+        }
+    });
 }
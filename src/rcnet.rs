@@ -0,0 +1,382 @@
+use std::collections::{HashSet, VecDeque};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use memflow::prelude::v1::*;
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+use crate::symbols::database::{FieldType, PrimitiveType, ProgramDatabase, TypeField};
+
+/// Version of the ReClass.NET file format.
+const FILE_VERSION: u32 = 0x0001_0001;
+/// Name of the xml document inside of a .rcnet archive.
+const DATA_FILE_NAME: &str = "Data.xml";
+
+/// Builds a ReClass.NET project from PDB types.
+///
+/// The requested type and all types embedded in it are converted to classes.
+/// Types which are only referenced through pointers are included up to `pointer_depth` levels deep,
+/// deeper pointers are exported as untyped pointers.
+pub struct Project<'a> {
+    database: &'a ProgramDatabase,
+    pointer_size: u64,
+    classes: Vec<String>,
+}
+
+impl<'a> Project<'a> {
+    pub fn new(
+        database: &'a ProgramDatabase,
+        type_name: &str,
+        pointer_depth: u32,
+        pointer_size: u64,
+    ) -> Self {
+        let mut classes = Vec::new();
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::new();
+        queue.push_back((type_name.to_string(), 0));
+        visited.insert(type_name.to_string());
+
+        while let Some((name, depth)) = queue.pop_front() {
+            if let Some(layout) = database.types.get(&name) {
+                for field in layout.fields.iter() {
+                    let mut references = Vec::new();
+                    collect_references(&field.ty, depth, &mut references);
+                    for (reference, reference_depth) in references.into_iter() {
+                        if reference_depth <= pointer_depth && visited.insert(reference.clone()) {
+                            queue.push_back((reference, reference_depth));
+                        }
+                    }
+                }
+                classes.push(name);
+            }
+        }
+
+        Self {
+            database,
+            pointer_size,
+            classes,
+        }
+    }
+
+    /// Writes the project either as a .rcnet archive or as plain xml if the file extension is `.xml`.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let xml = self.to_xml();
+
+        let is_xml = path
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("xml"))
+            .unwrap_or_default();
+        if is_xml {
+            std::fs::write(path, xml).map_err(|_| {
+                Error(ErrorOrigin::Other, ErrorKind::UnableToWriteFile)
+                    .log_error("unable to write project file")
+            })?;
+        } else {
+            let file = File::create(path).map_err(|_| {
+                Error(ErrorOrigin::Other, ErrorKind::UnableToWriteFile)
+                    .log_error("unable to create project file")
+            })?;
+            let mut zip = ZipWriter::new(file);
+            zip.start_file(
+                DATA_FILE_NAME,
+                FileOptions::default().compression_method(CompressionMethod::Stored),
+            )
+            .and_then(|_| zip.write_all(xml.as_bytes()).map_err(From::from))
+            .and_then(|_| zip.finish().map(|_| ()))
+            .map_err(|_| {
+                Error(ErrorOrigin::Other, ErrorKind::UnableToWriteFile)
+                    .log_error("unable to write project file")
+            })?;
+        }
+
+        Ok(())
+    }
+
+    pub fn to_xml(&self) -> String {
+        let platform = if self.pointer_size == 4 { "x86" } else { "x64" };
+
+        let mut xml = String::new();
+        xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        xml.push_str(&format!(
+            "<reclass version=\"{}\" platform=\"{}\">\n",
+            FILE_VERSION, platform
+        ));
+        xml.push_str("  <custom_data />\n");
+        xml.push_str("  <enums />\n");
+        xml.push_str("  <classes>\n");
+        for name in self.classes.iter() {
+            xml.push_str(&format!(
+                "    <class uuid=\"{}\" name=\"{}\" comment=\"\" address=\"0\">\n",
+                uuid(name),
+                escape(name)
+            ));
+            for node in self.class_nodes(name).iter() {
+                xml.push_str("      ");
+                xml.push_str(node);
+                xml.push('\n');
+            }
+            xml.push_str("    </class>\n");
+        }
+        xml.push_str("  </classes>\n");
+        xml.push_str("</reclass>\n");
+        xml
+    }
+
+    fn class_nodes(&self, name: &str) -> Vec<String> {
+        let layout = match self.database.types.get(name) {
+            Some(layout) => layout,
+            None => return Vec::new(),
+        };
+
+        let mut nodes = Vec::new();
+        let mut cursor = 0;
+        let mut fields = layout.fields.iter().peekable();
+        while let Some(field) = fields.next() {
+            if field.offset < cursor {
+                // overlapping union members cannot be represented in ReClass
+                continue;
+            }
+            nodes.extend(padding(cursor, field.offset));
+
+            if let FieldType::Bitfield(underlying, _, _) = &field.ty {
+                // all bitfields sharing the same storage unit are merged into one node
+                let mut members = vec![field];
+                while let Some(next) = fields.next_if(|f| {
+                    f.offset == field.offset && matches!(f.ty, FieldType::Bitfield(_, _, _))
+                }) {
+                    members.push(next);
+                }
+                nodes.push(bitfield_node(&members, underlying.size()));
+                cursor = field.offset + underlying.size();
+            } else {
+                nodes.extend(self.field_nodes(&field.name, &field.ty, ""));
+                cursor = field.offset + field.ty.size();
+            }
+        }
+        nodes.extend(padding(cursor, layout.size));
+
+        nodes
+    }
+
+    fn field_nodes(&self, name: &str, ty: &FieldType, comment: &str) -> Vec<String> {
+        match ty {
+            FieldType::Primitive(primitive) => primitive_nodes(name, *primitive, comment),
+            FieldType::Enum(enum_name, underlying) => primitive_nodes(name, *underlying, enum_name),
+            FieldType::Struct(struct_name, size) => {
+                if self.classes.contains(struct_name) {
+                    vec![class_instance_node(name, struct_name)]
+                } else {
+                    padding(0, *size)
+                }
+            }
+            FieldType::Pointer(target, size) => {
+                if *size != self.pointer_size {
+                    return padding(0, *size);
+                }
+                match target.as_ref() {
+                    FieldType::Primitive(PrimitiveType::Char) => {
+                        vec![node("Utf8TextPtrNode", name, comment, "", None)]
+                    }
+                    FieldType::Primitive(PrimitiveType::WideChar) => {
+                        vec![node("Utf16TextPtrNode", name, comment, "", None)]
+                    }
+                    FieldType::Function => vec![node("FunctionPtrNode", name, comment, "", None)],
+                    FieldType::Primitive(PrimitiveType::Void) => {
+                        vec![node("PointerNode", name, comment, "", None)]
+                    }
+                    FieldType::Struct(struct_name, _) if !self.classes.contains(struct_name) => {
+                        vec![node("PointerNode", name, struct_name, "", None)]
+                    }
+                    target => {
+                        let inner = self.field_nodes("", target, "");
+                        if inner.len() == 1 {
+                            vec![node(
+                                "PointerNode",
+                                name,
+                                comment,
+                                "",
+                                inner.first().cloned(),
+                            )]
+                        } else {
+                            vec![node("PointerNode", name, comment, "", None)]
+                        }
+                    }
+                }
+            }
+            FieldType::Array(element, count) => match element.as_ref() {
+                FieldType::Primitive(PrimitiveType::Char) => vec![node(
+                    "Utf8TextNode",
+                    name,
+                    comment,
+                    &format!(" length=\"{}\"", count),
+                    None,
+                )],
+                FieldType::Primitive(PrimitiveType::WideChar) => vec![node(
+                    "Utf16TextNode",
+                    name,
+                    comment,
+                    &format!(" length=\"{}\"", count),
+                    None,
+                )],
+                element => {
+                    let inner = self.field_nodes("", element, "");
+                    if inner.len() == 1 {
+                        vec![node(
+                            "ArrayNode",
+                            name,
+                            comment,
+                            &format!(" count=\"{}\"", count),
+                            inner.first().cloned(),
+                        )]
+                    } else {
+                        padding(0, ty.size())
+                    }
+                }
+            },
+            FieldType::Bitfield(underlying, _, _) => self.field_nodes(name, underlying, comment),
+            FieldType::Function => Vec::new(),
+        }
+    }
+}
+
+/// Collects all struct types referenced by `ty` together with the pointer depth they are found at.
+fn collect_references(ty: &FieldType, depth: u32, out: &mut Vec<(String, u32)>) {
+    match ty {
+        FieldType::Struct(name, _) => out.push((name.clone(), depth)),
+        FieldType::Pointer(target, _) => collect_references(target, depth + 1, out),
+        FieldType::Array(element, _) => collect_references(element, depth, out),
+        _ => {}
+    }
+}
+
+fn primitive_nodes(name: &str, primitive: PrimitiveType, comment: &str) -> Vec<String> {
+    let ty = match primitive {
+        PrimitiveType::Void => return Vec::new(),
+        PrimitiveType::Bool => "BoolNode",
+        PrimitiveType::Char | PrimitiveType::Int8 => "Int8Node",
+        PrimitiveType::UInt8 => "UInt8Node",
+        PrimitiveType::Int16 => "Int16Node",
+        PrimitiveType::WideChar | PrimitiveType::UInt16 => "UInt16Node",
+        PrimitiveType::Int32 => "Int32Node",
+        PrimitiveType::UInt32 => "UInt32Node",
+        PrimitiveType::Int64 => "Int64Node",
+        PrimitiveType::UInt64 => "UInt64Node",
+        PrimitiveType::Float => "FloatNode",
+        PrimitiveType::Double => "DoubleNode",
+        PrimitiveType::Other(size) => return padding(0, size),
+    };
+    vec![node(ty, name, comment, "", None)]
+}
+
+fn class_instance_node(name: &str, class_name: &str) -> String {
+    node(
+        "ClassInstanceNode",
+        name,
+        "",
+        &format!(" reference=\"{}\"", uuid(class_name)),
+        None,
+    )
+}
+
+fn bitfield_node(members: &[&TypeField], size: u64) -> String {
+    let name = members
+        .iter()
+        .map(|f| f.name.as_str())
+        .collect::<Vec<_>>()
+        .join("_");
+    let comment = members
+        .iter()
+        .filter_map(|f| match f.ty {
+            FieldType::Bitfield(_, position, length) => {
+                Some(format!("{}:{}:{}", f.name, position, length))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    node(
+        "BitFieldNode",
+        &name,
+        &comment,
+        &format!(" bits=\"{}\"", size * 8),
+        None,
+    )
+}
+
+/// Fills the gap between `from` and `to` with the largest fitting hex nodes.
+fn padding(from: u64, to: u64) -> Vec<String> {
+    let mut nodes = Vec::new();
+    let mut cursor = from;
+    while cursor < to {
+        let remaining = to - cursor;
+        let (ty, size) = if remaining >= 8 && cursor & 7 == 0 {
+            ("Hex64Node", 8)
+        } else if remaining >= 4 && cursor & 3 == 0 {
+            ("Hex32Node", 4)
+        } else if remaining >= 2 && cursor & 1 == 0 {
+            ("Hex16Node", 2)
+        } else {
+            ("Hex8Node", 1)
+        };
+        nodes.push(node(ty, "", "", "", None));
+        cursor += size;
+    }
+    nodes
+}
+
+fn node(ty: &str, name: &str, comment: &str, attributes: &str, inner: Option<String>) -> String {
+    let head = format!(
+        "<node type=\"{}\" name=\"{}\" comment=\"{}\" hidden=\"false\"{}",
+        ty,
+        escape(name),
+        escape(comment),
+        attributes
+    );
+    match inner {
+        Some(inner) => format!("{}>{}</node>", head, inner),
+        None => format!("{} />", head),
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Derives a stable class uuid from the type name so repeated exports reference the same classes.
+fn uuid(name: &str) -> String {
+    // two rounds of fnv-1a with different offset bases
+    let mut bytes = [0u8; 16];
+    for (chunk, basis) in bytes
+        .chunks_mut(8)
+        .zip([0xcbf2_9ce4_8422_2325u64, 0x6c62_272e_07bb_0142u64].iter())
+    {
+        let hash = name.bytes().fold(*basis, |hash, b| {
+            (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+        });
+        chunk.copy_from_slice(&hash.to_le_bytes());
+    }
+    base64(&bytes)
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - i * 8));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - i * 6)) as usize & 0x3f] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
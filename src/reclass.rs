@@ -1,5 +1,7 @@
 use std::ffi::c_void;
 
+use memflow::types::PageType;
+
 pub const MAX_PATH: usize = 260;
 
 pub type ProcessId = usize;
@@ -15,15 +17,11 @@ const _: [(); std::mem::size_of::<EnumerateProcessData>()] = [(); 0x418];
 
 impl EnumerateProcessData {
     pub fn new(pid: ProcessId, name: &str, path: &str) -> Self {
-        let name16 = name.encode_utf16().collect::<Vec<u16>>();
         let mut namebuf = [0u16; MAX_PATH];
-        namebuf[..name16.len().min(MAX_PATH)]
-            .copy_from_slice(&name16[..name16.len().min(MAX_PATH)]);
+        fill_utf16(&mut namebuf, name);
 
-        let path16 = path.encode_utf16().collect::<Vec<u16>>();
         let mut pathbuf = [0u16; MAX_PATH];
-        pathbuf[..path16.len().min(MAX_PATH)]
-            .copy_from_slice(&path16[..path16.len().min(MAX_PATH)]);
+        fill_utf16_path(&mut pathbuf, path);
 
         Self {
             pid,
@@ -46,15 +44,104 @@ pub struct EnumerateRemoteSectionData {
 const _: [(); std::mem::size_of::<EnumerateRemoteSectionData>()] = [(); 0x244];
 
 impl EnumerateRemoteSectionData {
-    pub fn new(base_address: *mut c_void, size: usize) -> Self {
+    pub fn new(
+        base_address: *mut c_void,
+        size: usize,
+        ty: SectionType,
+        category: SectionCategory,
+        protection: SectionProtection,
+        name: &str,
+        module_path: &str,
+    ) -> Self {
+        // names which do not fit are truncated
+        let mut namebuf = [0u16; 16];
+        fill_utf16(&mut namebuf, name);
+
+        let mut pathbuf = [0u16; MAX_PATH];
+        fill_utf16_path(&mut pathbuf, module_path);
+
         Self {
             base_address,
             size,
-            ty: 0,             // Unknown
-            category: 0,       // Unknown
-            protection: 1 | 2, // Read Write
-            name: [0u16; 16],
-            module_path: [0u16; MAX_PATH],
+            ty: ty as i32,
+            category: category as i32,
+            protection: protection.0,
+            name: namebuf,
+            module_path: pathbuf,
+        }
+    }
+}
+
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SectionType {
+    Unknown = 0,
+    Private = 1,
+    Mapped = 2,
+    Image = 3,
+}
+
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SectionCategory {
+    Unknown = 0,
+    Code = 1,
+    Data = 2,
+    Heap = 3,
+}
+
+/// `SectionProtection` flags of a section.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SectionProtection(i32);
+
+impl SectionProtection {
+    pub const READ: Self = Self(1);
+    pub const READ_WRITE: Self = Self(1 | 2);
+    pub const READ_EXECUTE: Self = Self(1 | 4);
+    pub const READ_WRITE_EXECUTE: Self = Self(1 | 2 | 4);
+
+    /// Converts the page type of mapped memory, unknown pages are assumed to be read-write.
+    pub fn from_page_type(page_type: PageType) -> Self {
+        if page_type.contains(PageType::UNKNOWN) {
+            return Self::READ_WRITE;
+        }
+        match (
+            page_type.contains(PageType::WRITEABLE),
+            page_type.contains(PageType::NOEXEC),
+        ) {
+            (false, true) => Self::READ,
+            (true, true) => Self::READ_WRITE,
+            (false, false) => Self::READ_EXECUTE,
+            (true, false) => Self::READ_WRITE_EXECUTE,
+        }
+    }
+
+    pub fn is_executable(self) -> bool {
+        self.0 & Self::READ_EXECUTE.0 == Self::READ_EXECUTE.0
+    }
+}
+
+#[repr(C, packed)]
+pub struct EnumerateRemoteThreadData {
+    id: usize,
+    teb: *mut c_void,
+    stack_base: *mut c_void,
+    stack_limit: *mut c_void,
+}
+const _: [(); std::mem::size_of::<EnumerateRemoteThreadData>()] = [(); 0x20];
+
+impl EnumerateRemoteThreadData {
+    pub fn new(
+        id: usize,
+        teb: *mut c_void,
+        stack_base: *mut c_void,
+        stack_limit: *mut c_void,
+    ) -> Self {
+        Self {
+            id,
+            teb,
+            stack_base,
+            stack_limit,
         }
     }
 }
@@ -69,10 +156,8 @@ const _: [(); std::mem::size_of::<EnumerateRemoteModuleData>()] = [(); 0x218];
 
 impl EnumerateRemoteModuleData {
     pub fn new(base_address: *mut c_void, size: usize, path: &str) -> Self {
-        let path16 = path.encode_utf16().collect::<Vec<u16>>();
         let mut pathbuf = [0u16; MAX_PATH];
-        pathbuf[..path16.len().min(MAX_PATH)]
-            .copy_from_slice(&path16[..path16.len().min(MAX_PATH)]);
+        fill_utf16_path(&mut pathbuf, path);
 
         Self {
             base_address,
@@ -82,6 +167,328 @@ impl EnumerateRemoteModuleData {
     }
 }
 
+#[repr(C, packed)]
+pub struct EnumerateRemoteSymbolData {
+    address: *mut c_void,
+    name: [u16; MAX_PATH],
+}
+const _: [(); std::mem::size_of::<EnumerateRemoteSymbolData>()] = [(); 0x210];
+
+impl EnumerateRemoteSymbolData {
+    pub fn new(address: *mut c_void, name: &str) -> Self {
+        let mut namebuf = [0u16; MAX_PATH];
+        fill_utf16(&mut namebuf, name);
+
+        Self {
+            address,
+            name: namebuf,
+        }
+    }
+}
+
+#[repr(C, packed)]
+pub struct EnumerateRemoteTypeFieldData {
+    offset: usize,
+    size: usize,
+    bit_position: i32, // -1 if the field is not a bitfield
+    bit_length: i32,
+    name: [u16; MAX_PATH],
+    type_name: [u16; MAX_PATH],
+}
+const _: [(); std::mem::size_of::<EnumerateRemoteTypeFieldData>()] = [(); 0x428];
+
+impl EnumerateRemoteTypeFieldData {
+    pub fn new(
+        offset: usize,
+        size: usize,
+        bitfield: Option<(u8, u8)>,
+        name: &str,
+        type_name: &str,
+    ) -> Self {
+        let mut namebuf = [0u16; MAX_PATH];
+        fill_utf16(&mut namebuf, name);
+
+        let mut typebuf = [0u16; MAX_PATH];
+        fill_utf16(&mut typebuf, type_name);
+
+        let (bit_position, bit_length) = bitfield
+            .map(|(position, length)| (position as i32, length as i32))
+            .unwrap_or((-1, 0));
+
+        Self {
+            offset,
+            size,
+            bit_position,
+            bit_length,
+            name: namebuf,
+            type_name: typebuf,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Default)]
+pub struct RegisterInfo {
+    pub rax: usize,
+    pub rbx: usize,
+    pub rcx: usize,
+    pub rdx: usize,
+    pub rdi: usize,
+    pub rsi: usize,
+    pub rsp: usize,
+    pub rbp: usize,
+    pub rip: usize,
+    pub r8: usize,
+    pub r9: usize,
+    pub r10: usize,
+    pub r11: usize,
+    pub r12: usize,
+    pub r13: usize,
+    pub r14: usize,
+    pub r15: usize,
+}
+
+#[repr(C)]
+pub struct ExceptionDebugInfo {
+    pub exception_code: usize,
+    pub exception_flags: usize,
+    pub exception_address: *mut c_void,
+    pub caused_by: i32, // enum HardwareBreakpointRegister
+    pub registers: RegisterInfo,
+}
+
+#[repr(C)]
+pub struct DebugEvent {
+    pub continue_status: i32, // enum DebugContinueStatus
+    pub process_id: ProcessId,
+    pub thread_id: usize,
+    pub exception_info: ExceptionDebugInfo,
+}
+const _: [(); std::mem::size_of::<DebugEvent>()] = [(); 0xC0];
+
+/// `EXCEPTION_SINGLE_STEP` which is raised by hardware breakpoints.
+pub const EXCEPTION_SINGLE_STEP: usize = 0x8000_0004;
+/// `HardwareBreakpointTrigger::Write`
+pub const HARDWARE_BREAKPOINT_WRITE: i32 = 2;
+
+impl DebugEvent {
+    /// Creates the event of a hardware breakpoint in register `caused_by` (`Dr0` = 1 to `Dr3` = 4).
+    ///
+    /// The thread, instruction address and registers are unknown and left empty.
+    pub fn breakpoint(process_id: ProcessId, caused_by: i32) -> Self {
+        Self {
+            continue_status: 0, // Handled
+            process_id,
+            thread_id: 0,
+            exception_info: ExceptionDebugInfo {
+                exception_code: EXCEPTION_SINGLE_STEP,
+                exception_flags: 0,
+                exception_address: std::ptr::null_mut(),
+                caused_by,
+                registers: RegisterInfo::default(),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ControlRemoteProcessAction {
+    Suspend,
+    Resume,
+    Terminate,
+}
+
+impl ControlRemoteProcessAction {
+    pub fn from_i32(action: i32) -> Option<Self> {
+        match action {
+            0 => Some(ControlRemoteProcessAction::Suspend),
+            1 => Some(ControlRemoteProcessAction::Resume),
+            2 => Some(ControlRemoteProcessAction::Terminate),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ControlRemoteProcessAction::Suspend => "suspend",
+            ControlRemoteProcessAction::Resume => "resume",
+            ControlRemoteProcessAction::Terminate => "terminate",
+        }
+    }
+}
+
 pub type EnumerateProcessCallback = extern "C" fn(*mut EnumerateProcessData);
 pub type EnumerateRemoteSectionsCallback = extern "C" fn(*mut EnumerateRemoteSectionData);
 pub type EnumerateRemoteModulesCallback = extern "C" fn(*mut EnumerateRemoteModuleData);
+pub type EnumerateRemoteThreadsCallback = extern "C" fn(*mut EnumerateRemoteThreadData);
+pub type EnumerateRemoteSymbolsCallback = extern "C" fn(*mut EnumerateRemoteSymbolData);
+pub type EnumerateRemoteTypeFieldsCallback = extern "C" fn(*mut EnumerateRemoteTypeFieldData);
+
+/// Copies a string into a null-terminated UTF-16 buffer provided by ReClass.NET.
+pub fn write_utf16(buffer: &mut [u16], s: &str) -> bool {
+    let s16 = s.encode_utf16().collect::<Vec<u16>>();
+    if s16.len() >= buffer.len() {
+        return false;
+    }
+    buffer[..s16.len()].copy_from_slice(&s16);
+    buffer[s16.len()] = 0;
+    true
+}
+
+/// Reads a null-terminated UTF-16 string provided by ReClass.NET.
+///
+/// # Safety
+///
+/// `ptr` must point to a valid null-terminated UTF-16 string.
+pub unsafe fn read_utf16(ptr: *const u16) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+    let mut len = 0;
+    while *ptr.add(len) != 0 {
+        len += 1;
+    }
+    String::from_utf16_lossy(std::slice::from_raw_parts(ptr, len))
+}
+
+/// Truncates a string to at most `len` UTF-16 code units.
+fn truncate(s: &str, len: usize) -> String {
+    let mut out = String::new();
+    let mut out_len = 0;
+    for c in s.chars() {
+        out_len += c.len_utf16();
+        if out_len > len {
+            break;
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Copies a string into a fixed size UTF-16 buffer which is always null-terminated.
+///
+/// Strings which do not fit are cut at a character boundary.
+fn fill_utf16(buffer: &mut [u16], s: &str) {
+    let truncated = truncate(s, buffer.len() - 1);
+    if truncated.len() < s.len() {
+        log::debug!("truncated `{}` to `{}`", s, truncated);
+    }
+    write_utf16(buffer, &truncated);
+}
+
+/// Copies a path into a fixed size UTF-16 buffer which is always null-terminated.
+///
+/// Paths which do not fit are shortened in the middle so the file name is kept.
+fn fill_utf16_path(buffer: &mut [u16], path: &str) {
+    let shortened = shorten_path(path, buffer.len() - 1);
+    if shortened != path {
+        log::debug!("shortened path `{}` to `{}`", path, shortened);
+    }
+    write_utf16(buffer, &shortened);
+}
+
+/// Shortens a path to at most `len` UTF-16 code units.
+///
+/// The directories closest to the file name are replaced by `...` until the path fits,
+/// e.g. `C:\Windows\...\module.dll`. If the file name alone is too long it is truncated.
+fn shorten_path(path: &str, len: usize) -> String {
+    const ELLIPSIS: &str = "...";
+
+    let utf16_len = |s: &str| s.encode_utf16().count();
+    if utf16_len(path) <= len {
+        return path.to_string();
+    }
+
+    let is_separator = |c: char| c == '\\' || c == '/';
+    let (directory, file_name) = match path.rfind(is_separator) {
+        Some(idx) => path.split_at(idx),
+        None => return truncate(path, len),
+    };
+
+    // `file_name` starts with its separator
+    let file_name_len = utf16_len(file_name);
+    if file_name_len + ELLIPSIS.len() >= len {
+        return truncate(&file_name[1..], len);
+    }
+
+    // keep whole directories of the prefix where possible
+    let mut prefix = truncate(directory, len - file_name_len - ELLIPSIS.len());
+    if let Some(idx) = prefix.rfind(is_separator) {
+        prefix.truncate(idx + 1);
+    }
+    format!("{}{}{}", prefix, ELLIPSIS, file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decodes a null-terminated UTF-16 buffer, panics if the terminator is missing.
+    fn decode(buffer: &[u16]) -> String {
+        let len = buffer
+            .iter()
+            .position(|&c| c == 0)
+            .expect("buffer is not null-terminated");
+        String::from_utf16(&buffer[..len]).expect("buffer contains split surrogate pairs")
+    }
+
+    #[test]
+    fn module_path_non_ascii() {
+        let path = r"C:\Program Files\Spiel\モジュール.dll";
+        let data = EnumerateRemoteModuleData::new(std::ptr::null_mut(), 0, path);
+        let buffer = data.path;
+        assert_eq!(decode(&buffer), path);
+    }
+
+    #[test]
+    fn module_path_keeps_file_name() {
+        let path = format!(r"C:\{}\bin\модуль.dll", "каталог\\".repeat(40));
+        let data = EnumerateRemoteModuleData::new(std::ptr::null_mut(), 0, &path);
+        let buffer = data.path;
+        let decoded = decode(&buffer);
+        assert!(decoded.encode_utf16().count() < MAX_PATH);
+        assert!(decoded.starts_with(r"C:\каталог\"));
+        assert!(decoded.ends_with(r"...\модуль.dll"));
+    }
+
+    #[test]
+    fn module_path_long_file_name() {
+        let file_name = "ü".repeat(MAX_PATH * 2);
+        let path = format!(r"C:\Windows\{}", file_name);
+        let data = EnumerateRemoteModuleData::new(std::ptr::null_mut(), 0, &path);
+        let buffer = data.path;
+        assert_eq!(decode(&buffer), "ü".repeat(MAX_PATH - 1));
+    }
+
+    #[test]
+    fn process_name_surrogate_pairs() {
+        // each emoji takes two UTF-16 code units and must not be split
+        let name = "🦀".repeat(MAX_PATH);
+        let data = EnumerateProcessData::new(0, &name, &name);
+        let buffer = data.name;
+        assert_eq!(decode(&buffer), "🦀".repeat((MAX_PATH - 1) / 2));
+        let buffer = data.path;
+        assert_eq!(decode(&buffer), "🦀".repeat((MAX_PATH - 1) / 2));
+    }
+
+    #[test]
+    fn section_name_truncated() {
+        let data = EnumerateRemoteSectionData::new(
+            std::ptr::null_mut(),
+            0,
+            SectionType::Image,
+            SectionCategory::Code,
+            SectionProtection::READ_EXECUTE,
+            "Ωmega section name",
+            "",
+        );
+        let buffer = data.name;
+        assert_eq!(decode(&buffer), "Ωmega section n");
+    }
+
+    #[test]
+    fn shorten_path_without_directories() {
+        assert_eq!(shorten_path("ä.dll", 5), "ä.dll");
+        assert_eq!(shorten_path("äbcdef.dll", 5), "äbcde");
+    }
+}
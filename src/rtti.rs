@@ -0,0 +1,163 @@
+use memflow::prelude::v1::*;
+
+/// Maximum length of a decorated type name.
+const MAX_NAME_LENGTH: usize = 0x200;
+
+/// Returns the demangled class name of the vtable at `vtable`.
+///
+/// The class is looked up through the MSVC `RTTICompleteObjectLocator` which is stored
+/// right in front of the first virtual function and references the `TypeDescriptor` of the class.
+pub fn class_name<T: MemoryView>(
+    mem: &mut T,
+    vtable: Address,
+    pointer_size: usize,
+) -> Option<String> {
+    let mut read =
+        |address: Address, buf: &mut [u8]| mem.read_raw_into(address, buf).data_part().is_ok();
+
+    // the first qword of an object without a vtable is often null, which is not a valid vtable
+    if vtable.is_null() {
+        return None;
+    }
+    let locator_ptr = vtable.to_umem().checked_sub(pointer_size as umem)?;
+
+    let mut buf = [0u8; 8];
+    if !read(Address::from(locator_ptr), &mut buf[..pointer_size]) {
+        return None;
+    }
+    let locator = Address::from(u64::from_le_bytes(buf));
+    type_name(&mut read, locator, pointer_size).map(|name| demangle(&name))
+}
+
+/// Lists all vtables with RTTI in a module together with their demangled class names.
+///
+/// The module image is read once and searched for pointers to valid complete object locators.
+pub fn module_classes<T: MemoryView>(
+    mem: &mut T,
+    module: &ModuleInfo,
+    pointer_size: usize,
+) -> Vec<(Address, String)> {
+    let mut image = vec![0u8; module.size as usize];
+    if mem
+        .read_raw_into(module.base, &mut image)
+        .data_part()
+        .is_err()
+    {
+        return Vec::new();
+    }
+
+    let (start, end) = (module.base.to_umem(), module.base.to_umem() + module.size);
+    let mut read = |address: Address, buf: &mut [u8]| {
+        let address = address.to_umem();
+        if address < start || address >= end {
+            return false;
+        }
+        // reads past the end of the image are filled with zeros like unmapped memory
+        let offset = (address - start) as usize;
+        let len = buf.len().min(image.len() - offset);
+        buf[..len].copy_from_slice(&image[offset..offset + len]);
+        buf[len..].iter_mut().for_each(|b| *b = 0);
+        true
+    };
+
+    let mut classes = Vec::new();
+    for offset in (0..(module.size as usize).saturating_sub(pointer_size)).step_by(pointer_size) {
+        let mut buf = [0u8; 8];
+        read(module.base + offset, &mut buf[..pointer_size]);
+        let value = u64::from_le_bytes(buf) as umem;
+        if value < start || value >= end {
+            continue;
+        }
+        if let Some(name) = type_name(&mut read, Address::from(value), pointer_size) {
+            classes.push((module.base + offset + pointer_size, demangle(&name)));
+        }
+    }
+    classes
+}
+
+/// Reads the decorated type name referenced by the complete object locator at `locator`.
+fn type_name<R: FnMut(Address, &mut [u8]) -> bool>(
+    read: &mut R,
+    locator: Address,
+    pointer_size: usize,
+) -> Option<String> {
+    // signature, offset, cdOffset, pTypeDescriptor, pClassDescriptor, pSelf
+    let mut col = [0u8; 24];
+    let col_size = if pointer_size == 8 { 24 } else { 20 };
+    if locator.is_null() || !read(locator, &mut col[..col_size]) {
+        return None;
+    }
+    let field = |idx: usize| {
+        u32::from_le_bytes([
+            col[idx * 4],
+            col[idx * 4 + 1],
+            col[idx * 4 + 2],
+            col[idx * 4 + 3],
+        ])
+    };
+
+    let type_descriptor = if pointer_size == 8 {
+        // 64 bit locators reference everything relative to the image base
+        // and contain their own rva to calculate it
+        if field(0) != 1 || field(5) as umem > locator.to_umem() {
+            return None;
+        }
+        let image_base = locator - field(5) as umem;
+        if image_base.to_umem() & 0xFFFF != 0 {
+            return None;
+        }
+        image_base.to_umem().checked_add(field(3) as umem)?
+    } else {
+        if field(0) != 0 {
+            return None;
+        }
+        field(3) as umem
+    };
+
+    // the decorated name follows the vtable pointer and an internal spare pointer
+    let name_address = type_descriptor.checked_add(2 * pointer_size as umem)?;
+    let mut name = [0u8; MAX_NAME_LENGTH];
+    if !read(Address::from(name_address), &mut name) {
+        return None;
+    }
+    let len = name.iter().position(|&c| c == 0)?;
+    let name = std::str::from_utf8(&name[..len]).ok()?;
+    if name.starts_with(".?AV") || name.starts_with(".?AU") {
+        Some(name.to_string())
+    } else {
+        None
+    }
+}
+
+/// Converts a decorated type name like `.?AVFoo@ns@@` to `ns::Foo`.
+///
+/// Templates and other complex names are returned in their decorated form.
+fn demangle(name: &str) -> String {
+    let body = name
+        .strip_prefix(".?AV")
+        .or_else(|| name.strip_prefix(".?AU"))
+        .and_then(|body| body.strip_suffix("@@"));
+
+    match body {
+        Some(body) if !body.is_empty() => {
+            let parts = body
+                .split('@')
+                .rev()
+                .map(|part| {
+                    if part.starts_with("?A0x") {
+                        Some("`anonymous namespace'")
+                    } else if part.is_empty() || part.starts_with('?') {
+                        None
+                    } else {
+                        Some(part)
+                    }
+                })
+                .collect::<Option<Vec<_>>>();
+            match parts {
+                Some(parts) => parts.join("::"),
+                None => name.to_string(),
+            }
+        }
+        _ => name.to_string(),
+    }
+}
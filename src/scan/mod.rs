@@ -0,0 +1,79 @@
+pub mod pattern;
+pub mod pointer;
+pub mod string;
+pub mod value;
+
+use crate::sections::Section;
+
+use memflow::prelude::v1::*;
+
+/// Size of the buffer used when reading memory regions.
+const CHUNK_SIZE: usize = 0x10_0000;
+
+/// Selects the memory regions to scan from the sections of the address space.
+///
+/// Regions can optionally be restricted to the address range of a module
+/// and to executable sections only.
+pub fn scan_regions(
+    sections: &[Section],
+    module: Option<&ModuleInfo>,
+    executable_only: bool,
+) -> Vec<(Address, umem)> {
+    sections
+        .iter()
+        .filter(|section| !executable_only || section.protection.is_executable())
+        .filter_map(|section| {
+            let (start, end) = (section.base, section.base + section.size);
+            match module {
+                Some(module) => {
+                    let start = start.max(module.base);
+                    let end = end.min(module.base + module.size);
+                    if start < end {
+                        Some((start, (end - start) as umem))
+                    } else {
+                        None
+                    }
+                }
+                None => Some((start, section.size)),
+            }
+        })
+        .collect()
+}
+
+/// Finds a module by its name or path.
+pub fn find_module<'a>(modules: &'a [ModuleInfo], name: &str) -> Option<&'a ModuleInfo> {
+    modules
+        .iter()
+        .find(|m| m.name.eq_ignore_ascii_case(name) || m.path.eq_ignore_ascii_case(name))
+}
+
+/// Reads all regions in chunks and calls `f` with the address and contents of each chunk.
+///
+/// Consecutive chunks overlap by `overlap` bytes so matches spanning a chunk boundary are found.
+/// Scanning stops early when `f` returns `false`.
+pub fn for_each_chunk<T: MemoryView, F: FnMut(Address, &[u8]) -> bool>(
+    mem: &mut T,
+    regions: &[(Address, umem)],
+    overlap: usize,
+    mut f: F,
+) {
+    let mut buf = vec![0u8; CHUNK_SIZE + overlap];
+    for (base, size) in regions.iter() {
+        let mut offset = 0;
+        while offset < *size {
+            let len = ((*size - offset) as usize).min(buf.len());
+            let chunk = &mut buf[..len];
+            chunk.iter_mut().for_each(|b| *b = 0);
+
+            let address = *base + offset;
+            if mem.read_raw_into(address, chunk).data_part().is_ok() && !f(address, chunk) {
+                return;
+            }
+
+            if len < buf.len() {
+                break;
+            }
+            offset += CHUNK_SIZE as umem;
+        }
+    }
+}
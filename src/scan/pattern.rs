@@ -0,0 +1,95 @@
+use memflow::prelude::v1::*;
+
+use super::for_each_chunk;
+
+/// An IDA-style byte pattern like `48 8B 05 ? ? ? ? 48 85 C0`.
+pub struct Pattern {
+    bytes: Vec<Option<u8>>,
+}
+
+impl Pattern {
+    /// Parses space separated hex bytes, `?` or `??` denote wildcards.
+    pub fn parse(pattern: &str) -> Result<Self> {
+        let bytes = pattern
+            .split_whitespace()
+            .map(|token| match token {
+                "?" | "??" => Ok(None),
+                _ if token.len() == 2 => u8::from_str_radix(token, 16).map(Some).map_err(|_| {
+                    Error(ErrorOrigin::Other, ErrorKind::InvalidArgument)
+                        .log_warn("invalid byte in pattern")
+                }),
+                _ => Err(Error(ErrorOrigin::Other, ErrorKind::InvalidArgument)
+                    .log_warn("invalid byte in pattern")),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if bytes.iter().all(|b| b.is_none()) {
+            return Err(Error(ErrorOrigin::Other, ErrorKind::InvalidArgument)
+                .log_warn("pattern must contain at least one byte"));
+        }
+
+        Ok(Self { bytes })
+    }
+
+    /// Creates a pattern matching the given bytes exactly.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self {
+            bytes: bytes.iter().copied().map(Some).collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn matches(&self, buf: &[u8]) -> bool {
+        self.bytes
+            .iter()
+            .zip(buf.iter())
+            .all(|(p, b)| p.map(|p| p == *b).unwrap_or(true))
+    }
+
+    /// Returns the offsets of all matches in `buf`.
+    pub fn find_all<'a>(&'a self, buf: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        // anchor the search on the first non-wildcard byte
+        let (anchor_idx, anchor) = self
+            .bytes
+            .iter()
+            .enumerate()
+            .find_map(|(i, b)| b.map(|b| (i, b)))
+            .unwrap_or_default();
+
+        let end = (buf.len() + 1).saturating_sub(self.len());
+        (0..end).filter(move |&i| buf[i + anchor_idx] == anchor && self.matches(&buf[i..]))
+    }
+}
+
+/// Scans the given regions for a pattern and returns up to `max_results` addresses.
+pub fn scan<T: MemoryView>(
+    mem: &mut T,
+    regions: &[(Address, umem)],
+    pattern: &Pattern,
+    max_results: usize,
+) -> Vec<Address> {
+    let mut results = Vec::new();
+    if max_results == 0 {
+        return results;
+    }
+    let overlap = pattern.len() - 1;
+    let mut last = Address::null();
+    for_each_chunk(mem, regions, overlap, |address, chunk| {
+        for offset in pattern.find_all(chunk) {
+            let found = address + offset as umem;
+            // matches in the overlapping area are reported by both chunks
+            if results.is_empty() || found > last {
+                results.push(found);
+                last = found;
+                if results.len() >= max_results {
+                    return false;
+                }
+            }
+        }
+        true
+    });
+    results
+}
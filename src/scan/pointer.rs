@@ -0,0 +1,313 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use memflow::prelude::v1::*;
+
+use super::{find_module, for_each_chunk};
+
+/// Upper bound of pointers tracked during a scan to keep memory usage in check.
+const MAX_NODES: usize = 4_000_000;
+/// Upper bound of pointers collected from the scanned memory, 256 MiB of entries.
+const MAX_POINTERS: usize = 16_000_000;
+
+#[derive(Clone, Copy, Debug)]
+pub struct PointerScanOptions {
+    /// Maximum number of pointers that are dereferenced in a chain.
+    pub max_depth: usize,
+    /// Maximum offset that is added to a dereferenced pointer.
+    pub max_offset: umem,
+    /// Maximum number of chains that are returned.
+    pub max_results: usize,
+}
+
+/// A pointer path from a static address in a module to a target address.
+///
+/// The chain is resolved by reading the pointer at `module + module_offset`,
+/// adding the first offset to it, dereferencing the result, adding the next offset and so on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PointerChain {
+    pub module: String,
+    pub module_offset: umem,
+    pub offsets: Vec<umem>,
+}
+
+impl PointerChain {
+    /// Follows the chain in the given memory and returns the address it currently points to.
+    pub fn resolve<T: MemoryView>(
+        &self,
+        mem: &mut T,
+        modules: &[ModuleInfo],
+        pointer_size: usize,
+    ) -> Option<Address> {
+        let module = find_module(modules, &self.module)?;
+        let mut address = module.base + self.module_offset;
+        for offset in self.offsets.iter() {
+            address = read_pointer(mem, address, pointer_size)? + *offset;
+        }
+        Some(address)
+    }
+}
+
+impl fmt::Display for PointerChain {
+    /// Formats the chain as `module+0x1234, 0x10, 0x8`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}+{:#x}", self.module, self.module_offset)?;
+        for offset in self.offsets.iter() {
+            write!(f, ", {:#x}", offset)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for PointerChain {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || {
+            Error(ErrorOrigin::Other, ErrorKind::InvalidArgument).log_warn("invalid pointer chain")
+        };
+
+        let mut parts = s.split(',').map(str::trim);
+        let (module, module_offset) = parts
+            .next()
+            .and_then(|base| base.rsplit_once('+'))
+            .ok_or_else(invalid)?;
+        let module_offset = parse_hex(module_offset).ok_or_else(invalid)?;
+        let offsets = parts
+            .map(|offset| parse_hex(offset).ok_or_else(invalid))
+            .collect::<Result<Vec<_>>>()?;
+        if module.is_empty() || offsets.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            module: module.to_string(),
+            module_offset,
+            offsets,
+        })
+    }
+}
+
+fn parse_hex(s: &str) -> Option<umem> {
+    umem::from_str_radix(s.trim_start_matches("0x"), 16).ok()
+}
+
+fn read_pointer<T: MemoryView>(
+    mem: &mut T,
+    address: Address,
+    pointer_size: usize,
+) -> Option<Address> {
+    let mut buf = [0u8; 8];
+    mem.read_raw_into(address, &mut buf[..pointer_size])
+        .data_part()
+        .ok()?;
+    Some(Address::from(u64::from_le_bytes(buf)))
+}
+
+/// Decodes a little endian pointer of 4 or 8 bytes.
+fn pointer_value(bytes: &[u8]) -> umem {
+    let mut buf = [0u8; 8];
+    buf[..bytes.len()].copy_from_slice(bytes);
+    u64::from_le_bytes(buf) as umem
+}
+
+/// All pointers found in the scanned memory as `(value, location)` pairs sorted by their value.
+struct PointerMap {
+    entries: Vec<(umem, umem)>,
+}
+
+impl PointerMap {
+    /// Collects all aligned values in `regions` that point into one of the regions.
+    ///
+    /// Fails if the regions contain more than `MAX_POINTERS` pointers.
+    fn new<T: MemoryView>(
+        mem: &mut T,
+        regions: &[(Address, umem)],
+        pointer_size: usize,
+    ) -> Result<Self> {
+        let mut entries = Vec::new();
+        let mut exceeded = false;
+        for_each_chunk(mem, regions, 0, |address, chunk| {
+            for (i, bytes) in chunk.chunks_exact(pointer_size).enumerate() {
+                let value = pointer_value(bytes);
+                if value != 0 && region_contains(regions, value) {
+                    if entries.len() >= MAX_POINTERS {
+                        exceeded = true;
+                        return false;
+                    }
+                    entries.push((value, address.to_umem() + (i * pointer_size) as umem));
+                }
+            }
+            true
+        });
+        if exceeded {
+            return Err(
+                Error(ErrorOrigin::Other, ErrorKind::OutOfBounds).log_warn(format!(
+                    "pointer scan found more than {} pointers, restrict the section range",
+                    MAX_POINTERS
+                )),
+            );
+        }
+        entries.sort_unstable();
+        Ok(Self { entries })
+    }
+
+    /// Returns all pointers whose value lies within `start..=end`.
+    fn pointing_to(&self, start: umem, end: umem) -> &[(umem, umem)] {
+        let from = self.entries.partition_point(|(value, _)| *value < start);
+        let to = self.entries.partition_point(|(value, _)| *value <= end);
+        &self.entries[from..to]
+    }
+}
+
+fn region_contains(regions: &[(Address, umem)], address: umem) -> bool {
+    let idx = regions.partition_point(|(base, _)| base.to_umem() <= address);
+    idx > 0 && {
+        let (base, size) = regions[idx - 1];
+        address < base.to_umem() + size
+    }
+}
+
+/// Searches `regions` for pointer chains that start in one of the `modules` and lead to `target`.
+///
+/// The search walks backwards from the target: each level looks for pointers
+/// to the previous level within `max_offset` bytes.
+/// Every pointer that is located inside of a module image completes a chain.
+/// The regions must be sorted by their base address.
+/// Fails if the regions contain too many pointers to be scanned.
+pub fn scan<T: MemoryView>(
+    mem: &mut T,
+    regions: &[(Address, umem)],
+    modules: &[ModuleInfo],
+    target: Address,
+    pointer_size: usize,
+    options: PointerScanOptions,
+) -> Result<Vec<PointerChain>> {
+    let map = PointerMap::new(mem, regions, pointer_size)?;
+
+    // each node is the location of a pointer, the offset added to its value and its parent node
+    let mut nodes = vec![(target.to_umem(), 0, 0)];
+    let mut visited = HashSet::new();
+    visited.insert(target.to_umem());
+
+    let mut results = Vec::new();
+    let mut level = 0..1;
+    for _ in 0..options.max_depth {
+        // no pointers lead to the previous level or no more pointers can be tracked
+        if level.is_empty() || nodes.len() >= MAX_NODES {
+            break;
+        }
+        let next = nodes.len();
+        for parent in level {
+            let address = nodes[parent].0;
+            let start = address.saturating_sub(options.max_offset);
+            for &(value, location) in map.pointing_to(start, address) {
+                if nodes.len() >= MAX_NODES || !visited.insert(location) {
+                    continue;
+                }
+                nodes.push((location, address - value, parent));
+
+                let module = modules.iter().find(|module| {
+                    location >= module.base.to_umem()
+                        && location < module.base.to_umem() + module.size
+                });
+                if let Some(module) = module {
+                    let mut offsets = Vec::new();
+                    let mut idx = nodes.len() - 1;
+                    while idx != 0 {
+                        offsets.push(nodes[idx].1);
+                        idx = nodes[idx].2;
+                    }
+                    results.push(PointerChain {
+                        module: module.name.to_string(),
+                        module_offset: location - module.base.to_umem(),
+                        offsets,
+                    });
+                    if results.len() >= options.max_results {
+                        return Ok(results);
+                    }
+                }
+            }
+        }
+        level = next..nodes.len();
+    }
+
+    if nodes.len() >= MAX_NODES {
+        log::warn!("pointer scan stopped after {} pointers", MAX_NODES);
+    }
+    Ok(results)
+}
+
+/// Finds all aligned pointers in `regions` which point to `target` or up to `max_offset` bytes before it.
+///
+/// Returns up to `max_results` locations together with the offset from the pointer to the target.
+pub fn find_references<T: MemoryView>(
+    mem: &mut T,
+    regions: &[(Address, umem)],
+    target: Address,
+    max_offset: umem,
+    pointer_size: usize,
+    max_results: usize,
+) -> Vec<(Address, umem)> {
+    let end = target.to_umem();
+    let start = end.saturating_sub(max_offset);
+
+    let mut results = Vec::new();
+    if max_results == 0 {
+        return results;
+    }
+    for_each_chunk(mem, regions, 0, |address, chunk| {
+        for (i, bytes) in chunk.chunks_exact(pointer_size).enumerate() {
+            let value = pointer_value(bytes);
+            if value >= start && value <= end {
+                results.push((address + (i * pointer_size) as umem, end - value));
+                if results.len() >= max_results {
+                    return false;
+                }
+            }
+        }
+        true
+    });
+    results
+}
+
+/// Returns the chains which still resolve to `target`.
+pub fn rescan<T: MemoryView>(
+    mem: &mut T,
+    modules: &[ModuleInfo],
+    chains: &[PointerChain],
+    target: Address,
+    pointer_size: usize,
+) -> Vec<PointerChain> {
+    chains
+        .iter()
+        .filter(|chain| chain.resolve(mem, modules, pointer_size) == Some(target))
+        .cloned()
+        .collect()
+}
+
+/// Writes the chains to a text file, one chain per line.
+pub fn save(path: &Path, chains: &[PointerChain]) -> Result<()> {
+    let text = chains
+        .iter()
+        .map(|chain| format!("{}\n", chain))
+        .collect::<String>();
+    std::fs::write(path, text).map_err(|_| {
+        Error(ErrorOrigin::Other, ErrorKind::UnableToWriteFile)
+            .log_error("unable to write pointer scan results")
+    })
+}
+
+/// Reads chains previously written by `save`.
+pub fn load(path: &Path) -> Result<Vec<PointerChain>> {
+    let text = std::fs::read_to_string(path).map_err(|_| {
+        Error(ErrorOrigin::Other, ErrorKind::UnableToReadFile)
+            .log_error("unable to read pointer scan results")
+    })?;
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
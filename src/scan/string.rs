@@ -0,0 +1,83 @@
+use memflow::prelude::v1::*;
+
+use super::pattern::{self, Pattern};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StringEncoding {
+    Ascii,
+    Utf16,
+}
+
+impl StringEncoding {
+    pub fn name(self) -> &'static str {
+        match self {
+            StringEncoding::Ascii => "ASCII",
+            StringEncoding::Utf16 => "UTF-16",
+        }
+    }
+
+    /// Encodes `text`, `None` is returned if it cannot be represented in this encoding.
+    fn encode(self, text: &str) -> Option<Vec<u8>> {
+        match self {
+            StringEncoding::Ascii if text.is_ascii() => Some(text.as_bytes().to_vec()),
+            StringEncoding::Ascii => None,
+            StringEncoding::Utf16 => Some(text.encode_utf16().flat_map(u16::to_le_bytes).collect()),
+        }
+    }
+}
+
+/// Searches the given regions for `text` in each of the `encodings`.
+///
+/// Returns up to `max_results` matches per encoding sorted by their address.
+pub fn find<T: MemoryView>(
+    mem: &mut T,
+    regions: &[(Address, umem)],
+    text: &str,
+    encodings: &[StringEncoding],
+    max_results: usize,
+) -> Result<Vec<(Address, StringEncoding)>> {
+    if text.is_empty() {
+        return Err(Error(ErrorOrigin::Other, ErrorKind::InvalidArgument)
+            .log_warn("search string must not be empty"));
+    }
+
+    let mut results = Vec::new();
+    for encoding in encodings.iter() {
+        let bytes = encoding.encode(text).ok_or_else(|| {
+            Error(ErrorOrigin::Other, ErrorKind::InvalidArgument).log_warn(format!(
+                "search string can not be encoded as {}",
+                encoding.name()
+            ))
+        })?;
+        let pattern = Pattern::from_bytes(&bytes);
+        results.extend(
+            pattern::scan(mem, regions, &pattern, max_results)
+                .into_iter()
+                .map(|address| (address, *encoding)),
+        );
+    }
+    results.sort_by_key(|(address, _)| *address);
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_ascii() {
+        assert_eq!(
+            StringEncoding::Ascii.encode("Health"),
+            Some(b"Health".to_vec())
+        );
+        assert_eq!(StringEncoding::Ascii.encode("Größe"), None);
+    }
+
+    #[test]
+    fn encode_utf16() {
+        assert_eq!(
+            StringEncoding::Utf16.encode("Hü"),
+            Some(vec![b'H', 0, 0xfc, 0])
+        );
+    }
+}
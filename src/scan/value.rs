@@ -0,0 +1,381 @@
+use std::cmp::Ordering;
+
+use memflow::prelude::v1::*;
+
+use super::{for_each_chunk, pattern::Pattern};
+
+/// Upper bound of results kept by a scan to keep memory usage in check.
+const MAX_RESULTS: usize = 10_000_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueType {
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    Float,
+    Double,
+    String,
+    ByteArray,
+}
+
+impl ValueType {
+    pub const ALL: [ValueType; 8] = [
+        ValueType::Int8,
+        ValueType::Int16,
+        ValueType::Int32,
+        ValueType::Int64,
+        ValueType::Float,
+        ValueType::Double,
+        ValueType::String,
+        ValueType::ByteArray,
+    ];
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ValueType::Int8 => "Int8",
+            ValueType::Int16 => "Int16",
+            ValueType::Int32 => "Int32",
+            ValueType::Int64 => "Int64",
+            ValueType::Float => "Float",
+            ValueType::Double => "Double",
+            ValueType::String => "String",
+            ValueType::ByteArray => "Byte Array",
+        }
+    }
+
+    /// Returns the size of numeric types, strings and byte arrays have a variable size.
+    fn size(self) -> Option<usize> {
+        match self {
+            ValueType::Int8 => Some(1),
+            ValueType::Int16 => Some(2),
+            ValueType::Int32 | ValueType::Float => Some(4),
+            ValueType::Int64 | ValueType::Double => Some(8),
+            ValueType::String | ValueType::ByteArray => None,
+        }
+    }
+
+    /// Formats a value of this type for display.
+    fn format(self, bytes: &[u8]) -> String {
+        match self {
+            ValueType::String => String::from_utf8_lossy(bytes).into_owned(),
+            ValueType::ByteArray => bytes
+                .iter()
+                .map(|b| format!("{:02X}", b))
+                .collect::<Vec<_>>()
+                .join(" "),
+            _ => match Number::read(self, bytes) {
+                Number::Int(value) => value.to_string(),
+                Number::Float(value) => value.to_string(),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompareType {
+    Exact,
+    Range,
+    Changed,
+    Unchanged,
+    Increased,
+    Decreased,
+}
+
+impl CompareType {
+    pub const ALL: [CompareType; 6] = [
+        CompareType::Exact,
+        CompareType::Range,
+        CompareType::Changed,
+        CompareType::Unchanged,
+        CompareType::Increased,
+        CompareType::Decreased,
+    ];
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CompareType::Exact => "Exact Value",
+            CompareType::Range => "Value Between",
+            CompareType::Changed => "Changed Value",
+            CompareType::Unchanged => "Unchanged Value",
+            CompareType::Increased => "Increased Value",
+            CompareType::Decreased => "Decreased Value",
+        }
+    }
+}
+
+/// A numeric value entered by the user.
+#[derive(Clone, Copy, Debug)]
+pub enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+impl Number {
+    fn parse(ty: ValueType, s: &str) -> Result<Self> {
+        let s = s.trim();
+        let number = match ty {
+            ValueType::Float | ValueType::Double => s.parse::<f64>().ok().map(Number::Float),
+            _ => match s.strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16).ok().map(|v| v as i64),
+                None => s
+                    .parse::<i64>()
+                    .ok()
+                    .or_else(|| s.parse::<u64>().ok().map(|v| v as i64)),
+            }
+            .map(Number::Int),
+        };
+        number.ok_or_else(|| {
+            Error(ErrorOrigin::Other, ErrorKind::InvalidArgument).log_warn("invalid scan value")
+        })
+    }
+
+    fn read(ty: ValueType, bytes: &[u8]) -> Self {
+        let mut buf = [0u8; 8];
+        let len = ty.size().unwrap_or_default().min(bytes.len());
+        buf[..len].copy_from_slice(&bytes[..len]);
+        match ty {
+            ValueType::Int8 => Number::Int(buf[0] as i8 as i64),
+            ValueType::Int16 => Number::Int(i16::from_le_bytes([buf[0], buf[1]]) as i64),
+            ValueType::Int32 => {
+                Number::Int(i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as i64)
+            }
+            ValueType::Float => {
+                Number::Float(f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64)
+            }
+            ValueType::Double => Number::Float(f64::from_le_bytes(buf)),
+            _ => Number::Int(i64::from_le_bytes(buf)),
+        }
+    }
+
+    /// Truncates an integer to the width of `ty` so unsigned input matches the signed value in memory.
+    fn truncate(self, ty: ValueType) -> Self {
+        match self {
+            Number::Int(value) => Number::Int(match ty {
+                ValueType::Int8 => value as i8 as i64,
+                ValueType::Int16 => value as i16 as i64,
+                ValueType::Int32 => value as i32 as i64,
+                _ => value,
+            }),
+            number => number,
+        }
+    }
+
+    fn compare(self, other: Self) -> Option<Ordering> {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => Some(a.cmp(&b)),
+            (Number::Float(a), Number::Float(b)) => a.partial_cmp(&b),
+            _ => None,
+        }
+    }
+}
+
+/// A condition a value has to fulfill to remain in the scan results.
+pub enum Condition {
+    Equal(Number),
+    Between(Number, Number),
+    Matches(Pattern),
+    Changed,
+    Unchanged,
+    Increased,
+    Decreased,
+}
+
+impl Condition {
+    /// Creates the condition for `compare` from the values entered by the user.
+    ///
+    /// `value` is the value to compare against and `value2` the upper bound of a range.
+    /// Byte arrays are entered as IDA-style patterns and may contain wildcards.
+    pub fn parse(ty: ValueType, compare: CompareType, value: &str, value2: &str) -> Result<Self> {
+        let invalid = |msg| Error(ErrorOrigin::Other, ErrorKind::InvalidArgument).log_warn(msg);
+
+        let numeric = ty.size().is_some();
+        match compare {
+            CompareType::Exact => match ty {
+                ValueType::String if value.is_empty() => Err(invalid("empty scan string")),
+                ValueType::String => Ok(Condition::Matches(Pattern::from_bytes(value.as_bytes()))),
+                ValueType::ByteArray => Pattern::parse(value).map(Condition::Matches),
+                ValueType::Float | ValueType::Double => {
+                    // match all values which are displayed like the entered value
+                    let decimals = value
+                        .trim()
+                        .split_once('.')
+                        .map(|(_, fraction)| fraction.len())
+                        .unwrap_or_default();
+                    let tolerance = 0.5 / 10f64.powi(decimals as i32);
+                    match Number::parse(ty, value)? {
+                        Number::Float(value) => Ok(Condition::Between(
+                            Number::Float(value - tolerance),
+                            Number::Float(value + tolerance),
+                        )),
+                        number => Ok(Condition::Equal(number)),
+                    }
+                }
+                _ => Ok(Condition::Equal(Number::parse(ty, value)?.truncate(ty))),
+            },
+            CompareType::Range if numeric => Ok(Condition::Between(
+                Number::parse(ty, value)?,
+                Number::parse(ty, value2)?,
+            )),
+            CompareType::Changed => Ok(Condition::Changed),
+            CompareType::Unchanged => Ok(Condition::Unchanged),
+            CompareType::Increased if numeric => Ok(Condition::Increased),
+            CompareType::Decreased if numeric => Ok(Condition::Decreased),
+            _ => Err(invalid("comparison is only supported for numeric values")),
+        }
+    }
+
+    /// Returns true if the condition compares against the previous scan.
+    fn requires_previous(&self) -> bool {
+        !matches!(
+            self,
+            Condition::Equal(_) | Condition::Between(_, _) | Condition::Matches(_)
+        )
+    }
+
+    fn matches(&self, ty: ValueType, current: &[u8], previous: &[u8]) -> bool {
+        let ordering = || Number::read(ty, current).compare(Number::read(ty, previous));
+        match self {
+            Condition::Equal(value) => {
+                Number::read(ty, current).compare(*value) == Some(Ordering::Equal)
+            }
+            Condition::Between(low, high) => {
+                let current = Number::read(ty, current);
+                matches!(
+                    current.compare(*low),
+                    Some(Ordering::Greater) | Some(Ordering::Equal)
+                ) && matches!(
+                    current.compare(*high),
+                    Some(Ordering::Less) | Some(Ordering::Equal)
+                )
+            }
+            Condition::Matches(pattern) => pattern.matches(current),
+            Condition::Changed => current != previous,
+            Condition::Unchanged => current == previous,
+            Condition::Increased => ordering() == Some(Ordering::Greater),
+            Condition::Decreased => ordering() == Some(Ordering::Less),
+        }
+    }
+}
+
+/// The state of a value scan which is refined by subsequent scans.
+pub struct ValueScan {
+    ty: ValueType,
+    value_size: usize,
+    addresses: Vec<Address>,
+    /// The values of all results at the time of the last scan, `value_size` bytes each.
+    values: Vec<u8>,
+}
+
+impl ValueScan {
+    /// Scans the given regions for all values matching `condition`.
+    ///
+    /// The initial scan can only search for exact values or ranges.
+    /// Numeric values are expected to be aligned to their size (or 4 bytes for 64 bit values).
+    pub fn first<T: MemoryView>(
+        mem: &mut T,
+        regions: &[(Address, umem)],
+        ty: ValueType,
+        condition: &Condition,
+    ) -> Result<Self> {
+        if condition.requires_previous() {
+            return Err(Error(ErrorOrigin::Other, ErrorKind::InvalidArgument)
+                .log_warn("the first scan requires a value to search for"));
+        }
+
+        let (value_size, alignment) = match (ty.size(), condition) {
+            (Some(size), _) => (size, size.min(4)),
+            (None, Condition::Matches(pattern)) => (pattern.len(), 1),
+            _ => {
+                return Err(Error(ErrorOrigin::Other, ErrorKind::InvalidArgument)
+                    .log_warn("invalid condition for value type"))
+            }
+        };
+
+        let mut scan = Self {
+            ty,
+            value_size,
+            addresses: Vec::new(),
+            values: Vec::new(),
+        };
+
+        let mut last = Address::null();
+        for_each_chunk(mem, regions, value_size - 1, |address, chunk| {
+            let end = (chunk.len() + 1).saturating_sub(value_size);
+            for offset in (0..end).step_by(alignment) {
+                let value = &chunk[offset..offset + value_size];
+                let found = address + offset as umem;
+                // matches in the overlapping area are reported by both chunks
+                if (scan.addresses.is_empty() || found > last) && condition.matches(ty, value, &[])
+                {
+                    scan.addresses.push(found);
+                    scan.values.extend_from_slice(value);
+                    last = found;
+                    if scan.addresses.len() >= MAX_RESULTS {
+                        log::warn!("value scan stopped after {} results", MAX_RESULTS);
+                        return false;
+                    }
+                }
+            }
+            true
+        });
+
+        Ok(scan)
+    }
+
+    /// Re-reads all results and only keeps the ones matching `condition`.
+    pub fn next<T: MemoryView>(&mut self, mem: &mut T, condition: &Condition) -> Result<()> {
+        if let Condition::Matches(pattern) = condition {
+            if pattern.len() != self.value_size {
+                return Err(Error(ErrorOrigin::Other, ErrorKind::InvalidArgument)
+                    .log_warn("the value must have the same length as in the first scan"));
+            }
+        }
+
+        let mut current = vec![0u8; self.value_size];
+        let mut kept = 0;
+        for idx in 0..self.addresses.len() {
+            let address = self.addresses[idx];
+            let previous = idx * self.value_size..(idx + 1) * self.value_size;
+            // partial reads are dropped, the unread bytes would still hold the previous value
+            if mem.read_raw_into(address, &mut current).is_ok()
+                && condition.matches(self.ty, &current, &self.values[previous])
+            {
+                self.addresses[kept] = address;
+                self.values[kept * self.value_size..(kept + 1) * self.value_size]
+                    .copy_from_slice(&current);
+                kept += 1;
+            }
+        }
+        self.addresses.truncate(kept);
+        self.values.truncate(kept * self.value_size);
+
+        Ok(())
+    }
+
+    pub fn value_type(&self) -> ValueType {
+        self.ty
+    }
+
+    pub fn len(&self) -> usize {
+        self.addresses.len()
+    }
+
+    /// Returns up to `max_results` addresses together with their formatted value from the last scan.
+    pub fn results(&self, max_results: usize) -> Vec<(Address, String)> {
+        self.addresses
+            .iter()
+            .zip(self.values.chunks_exact(self.value_size))
+            .take(max_results)
+            .map(|(address, value)| (*address, self.ty.format(value)))
+            .collect()
+    }
+}
@@ -0,0 +1,136 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use memflow::prelude::v1::*;
+
+use crate::sections::Section;
+
+/// State shared between a `SectionCache` and its background worker.
+#[derive(Default)]
+struct Shared {
+    sections: Mutex<Option<Vec<Section>>>,
+    /// Set when the cached sections are outdated and have to be refreshed immediately.
+    invalidated: Mutex<bool>,
+    wakeup: Condvar,
+    running: AtomicBool,
+}
+
+/// The latest known section map of a handle which is refreshed by a background worker.
+///
+/// The worker is stopped once the cache is dropped, the cache is marked as stopped
+/// once the worker exits.
+pub struct SectionCache {
+    shared: Arc<Shared>,
+    /// Base and size of the modules the section map belongs to.
+    modules: Vec<(Address, umem)>,
+}
+
+impl SectionCache {
+    pub fn new() -> Self {
+        let shared = Shared {
+            running: AtomicBool::new(true),
+            ..Default::default()
+        };
+        Self {
+            shared: Arc::new(shared),
+            modules: Vec::new(),
+        }
+    }
+
+    /// Returns the handle the background worker uses to publish new section maps.
+    pub fn worker(&self) -> SectionWorker {
+        SectionWorker {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Returns `true` once the worker exited and no longer refreshes the section map.
+    pub fn stopped(&self) -> bool {
+        !self.shared.running.load(Ordering::Relaxed)
+    }
+
+    /// Returns the latest section map or `None` if the worker did not finish its first run yet.
+    pub fn sections(&self) -> Option<Vec<Section>> {
+        self.shared
+            .sections
+            .lock()
+            .ok()
+            .and_then(|sections| sections.clone())
+    }
+
+    /// Refreshes the section map immediately if the module list changed since the last call.
+    pub fn update_modules(&mut self, modules: &[ModuleInfo]) {
+        let modules = modules
+            .iter()
+            .map(|module| (module.base, module.size))
+            .collect::<Vec<_>>();
+        if modules != self.modules {
+            if !self.modules.is_empty() {
+                log::debug!("module list changed, refreshing sections");
+                self.invalidate();
+            }
+            self.modules = modules;
+        }
+    }
+
+    /// Wakes up the worker to refresh the section map.
+    pub fn invalidate(&self) {
+        if let Ok(mut invalidated) = self.shared.invalidated.lock() {
+            *invalidated = true;
+            self.shared.wakeup.notify_all();
+        }
+    }
+}
+
+impl Drop for SectionCache {
+    fn drop(&mut self) {
+        // hold the lock so the worker cannot miss the wakeup between its checks and waiting
+        let _invalidated = self.shared.invalidated.lock();
+        self.shared.running.store(false, Ordering::Relaxed);
+        self.shared.wakeup.notify_all();
+    }
+}
+
+/// The background worker side of a `SectionCache`.
+pub struct SectionWorker {
+    shared: Arc<Shared>,
+}
+
+impl Drop for SectionWorker {
+    fn drop(&mut self) {
+        self.shared.running.store(false, Ordering::Relaxed);
+    }
+}
+
+impl SectionWorker {
+    /// Returns `false` once the cache has been dropped and the worker should stop.
+    pub fn running(&self) -> bool {
+        self.shared.running.load(Ordering::Relaxed)
+    }
+
+    /// Publishes a new section map.
+    pub fn store(&self, sections: Vec<Section>) {
+        if let Ok(mut cached) = self.shared.sections.lock() {
+            *cached = Some(sections);
+        }
+    }
+
+    /// Waits until the refresh interval elapsed, the cache is invalidated or dropped.
+    ///
+    /// Returns `false` if the worker should stop.
+    pub fn wait(&self, interval: Duration) -> bool {
+        if let Ok(invalidated) = self.shared.invalidated.lock() {
+            let result =
+                self.shared
+                    .wakeup
+                    .wait_timeout_while(invalidated, interval, |invalidated| {
+                        !*invalidated && self.running()
+                    });
+            if let Ok((mut invalidated, _)) = result {
+                *invalidated = false;
+            }
+        }
+        self.running()
+    }
+}
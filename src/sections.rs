@@ -0,0 +1,520 @@
+use crate::gui::Config;
+use crate::memflow_wrapper::Target;
+use crate::reclass::{EnumerateRemoteSectionData, SectionCategory, SectionProtection, SectionType};
+
+use std::ffi::c_void;
+use std::time::{Duration, Instant};
+
+use memflow::prelude::v1::*;
+
+/// A section as it is reported to ReClass.NET.
+#[derive(Clone, Debug)]
+pub struct Section {
+    pub base: Address,
+    pub size: umem,
+    pub ty: SectionType,
+    pub category: SectionCategory,
+    pub protection: SectionProtection,
+    pub name: String,
+    /// Path of the module the section belongs to.
+    pub module_path: String,
+}
+
+impl Section {
+    /// Creates an unnamed section of mapped memory, executable memory is categorized as code.
+    pub fn new(base: Address, size: umem, protection: SectionProtection) -> Self {
+        let category = if protection.is_executable() {
+            SectionCategory::Code
+        } else {
+            SectionCategory::Data
+        };
+        Self {
+            base,
+            size,
+            ty: SectionType::Unknown,
+            category,
+            protection,
+            name: String::new(),
+            module_path: String::new(),
+        }
+    }
+
+    pub fn named(
+        base: Address,
+        size: umem,
+        ty: SectionType,
+        category: SectionCategory,
+        name: String,
+    ) -> Self {
+        Self {
+            base,
+            size,
+            ty,
+            category,
+            protection: SectionProtection::READ_WRITE,
+            name,
+            module_path: String::new(),
+        }
+    }
+
+    pub fn to_reclass(&self) -> EnumerateRemoteSectionData {
+        EnumerateRemoteSectionData::new(
+            self.base.to_umem() as *mut c_void,
+            self.size as usize,
+            self.ty,
+            self.category,
+            self.protection,
+            &self.name,
+            &self.module_path,
+        )
+    }
+
+    fn end(&self) -> Address {
+        self.base + self.size
+    }
+}
+
+/// Start of the canonical kernel half of the 64 bit address space.
+const KERNEL_START: umem = 0xFFFF_8000_0000_0000;
+/// Size of the address ranges which are walked at once, one top level page table entry.
+const WALK_SIZE: umem = 1 << 39;
+
+/// Limits of the section enumeration.
+#[derive(Clone, Copy, Debug)]
+pub struct SectionOptions {
+    /// Mapped pages which are less than `gap` bytes apart are merged into a single section.
+    pub gap: umem,
+    /// Start of the user mode address range that is enumerated.
+    pub start: Address,
+    /// End of the user mode address range that is enumerated.
+    pub end: Address,
+    /// Sections smaller than this are dropped.
+    pub min_size: umem,
+    /// Maximum number of sections that are returned.
+    pub max_count: usize,
+    /// Maximum time spent walking the page tables.
+    pub timeout: Duration,
+}
+
+impl From<&Config> for SectionOptions {
+    fn from(config: &Config) -> Self {
+        Self {
+            gap: config.section_gap,
+            start: parse_address(&config.section_start).unwrap_or_else(Address::null),
+            end: parse_address(&config.section_end).unwrap_or_else(Address::invalid),
+            min_size: config.section_min_size,
+            max_count: config.section_max_count as usize,
+            timeout: Duration::from_millis(config.section_timeout),
+        }
+    }
+}
+
+/// Parses a hex address with an optional `0x` prefix.
+fn parse_address(address: &str) -> Option<Address> {
+    let address = address.trim();
+    let address = address.strip_prefix("0x").unwrap_or(address);
+    umem::from_str_radix(address, 16).ok().map(Address::from)
+}
+
+/// Returns the sections of a target.
+///
+/// The configured address range only applies to processes, the entire kernel half
+/// of the address space is enumerated for the kernel.
+pub fn enumerate(target: &mut dyn Target, options: &SectionOptions) -> Vec<Section> {
+    let (start, end) = if target.is_kernel() {
+        (KERNEL_START.into(), Address::invalid())
+    } else {
+        (options.start, options.end)
+    };
+    enumerate_range(target, options, start, end)
+}
+
+/// Walks the mapped memory between `start` and `end` and merges it into sections.
+///
+/// Only memory with the same protection is merged. The range is walked in steps so the
+/// enumeration can stop once the timeout elapsed or the maximum number of sections has been found.
+fn enumerate_range(
+    target: &mut dyn Target,
+    options: &SectionOptions,
+    start: Address,
+    end: Address,
+) -> Vec<Section> {
+    let deadline = Instant::now() + options.timeout;
+
+    let mut sections: Vec<Section> = Vec::new();
+    let mut walk_start = start.to_umem();
+    while walk_start < end.to_umem() {
+        if Instant::now() >= deadline {
+            log::warn!(
+                "section enumeration timed out at {:x}, the remaining sections are omitted",
+                walk_start
+            );
+            break;
+        }
+        if sections.len() > options.max_count {
+            break;
+        }
+
+        let walk_end = (walk_start - walk_start % WALK_SIZE)
+            .saturating_add(WALK_SIZE)
+            .min(end.to_umem());
+        let mut maps =
+            target.mapped_ranges(options.gap as imem, walk_start.into(), walk_end.into());
+        maps.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        merge_ranges(&mut sections, &maps, options.gap);
+
+        walk_start = walk_end;
+    }
+
+    sections.retain(|section| section.size >= options.min_size);
+    if sections.len() > options.max_count {
+        log::warn!(
+            "found more than {} sections, the remaining sections are omitted",
+            options.max_count
+        );
+        sections.truncate(options.max_count);
+    }
+    sections
+}
+
+/// Appends mapped ranges sorted by address to a list of sections.
+///
+/// A range is merged into the last section if it has the same protection
+/// and is at most `gap` bytes apart from it.
+fn merge_ranges(sections: &mut Vec<Section>, maps: &[MemoryRange], gap: umem) {
+    for map in maps.iter() {
+        let protection = SectionProtection::from_page_type(map.2);
+        match sections.last_mut() {
+            Some(section)
+                if section.protection == protection && (map.0 - section.end()) as umem <= gap =>
+            {
+                section.size = (map.0 + map.1 - section.base) as umem
+            }
+            _ => sections.push(Section::new(map.0, map.1, protection)),
+        }
+    }
+}
+
+/// Returns the address ranges of all sections of a target.
+pub fn regions(target: &mut dyn Target, options: &SectionOptions) -> Vec<(Address, umem)> {
+    enumerate(target, options)
+        .into_iter()
+        .map(|section| (section.base, section.size))
+        .collect()
+}
+
+/// Inserts a section into a sorted list of sections.
+///
+/// Existing sections which overlap the new section are cut at its boundaries
+/// so the new section replaces the overlapping part.
+pub fn insert(sections: &mut Vec<Section>, section: Section) {
+    if section.size == 0 {
+        return;
+    }
+
+    let mut result = Vec::with_capacity(sections.len() + 2);
+    for existing in sections.drain(..) {
+        if existing.end() <= section.base || existing.base >= section.end() {
+            result.push(existing);
+            continue;
+        }
+        if existing.base < section.base {
+            let mut head = existing.clone();
+            head.size = (section.base - existing.base) as umem;
+            result.push(head);
+        }
+        if existing.end() > section.end() {
+            let mut tail = existing.clone();
+            tail.base = section.end();
+            tail.size = (existing.end() - section.end()) as umem;
+            result.push(tail);
+        }
+    }
+
+    let idx = result
+        .iter()
+        .position(|existing| existing.base > section.base)
+        .unwrap_or(result.len());
+    result.insert(idx, section);
+    *sections = result;
+}
+
+/// Applies the type, category and name of `label` to the parts of all sections it overlaps.
+///
+/// Unlike `insert` this does not add unmapped memory of `label` to the list,
+/// sections are only cut at its boundaries.
+pub fn label(sections: &mut Vec<Section>, label: &Section) {
+    split_apply(sections, label.base, label.size, |section| {
+        section.ty = label.ty;
+        section.category = label.category;
+        section.name = label.name.clone();
+    });
+}
+
+/// Attributes the parts of all sections which overlap a module to that module.
+///
+/// Sections are split at the module boundaries, the parts inside a module are marked
+/// as image sections and named after the module unless they already have a name.
+pub fn label_modules(sections: &mut Vec<Section>, modules: &[ModuleInfo]) {
+    for module in modules.iter() {
+        split_apply(sections, module.base, module.size, |section| {
+            section.ty = SectionType::Image;
+            if section.name.is_empty() {
+                section.name = module.name.to_string();
+            }
+            section.module_path = module.path.to_string();
+        });
+    }
+}
+
+/// Names the parts of all sections which overlap a mapped file after the file.
+///
+/// Unlike `label_modules` the parts are marked as mapped memory and are not attributed to a module.
+pub fn label_mappings(sections: &mut Vec<Section>, mappings: &[ModuleInfo]) {
+    for mapping in mappings.iter() {
+        split_apply(sections, mapping.base, mapping.size, |section| {
+            section.ty = SectionType::Mapped;
+            if section.name.is_empty() {
+                section.name = mapping.name.to_string();
+            }
+        });
+    }
+}
+
+/// Cuts all sections at the boundaries of `base..base + size` and applies `f` to the parts inside.
+fn split_apply<F: FnMut(&mut Section)>(
+    sections: &mut Vec<Section>,
+    base: Address,
+    size: umem,
+    mut f: F,
+) {
+    let end = base + size;
+    let mut result = Vec::with_capacity(sections.len() + 2);
+    for existing in sections.drain(..) {
+        if existing.end() <= base || existing.base >= end {
+            result.push(existing);
+            continue;
+        }
+
+        let part_base = existing.base.max(base);
+        let part_end = existing.end().min(end);
+        if existing.base < part_base {
+            let mut head = existing.clone();
+            head.size = (part_base - existing.base) as umem;
+            result.push(head);
+        }
+        let mut part = existing.clone();
+        part.base = part_base;
+        part.size = (part_end - part_base) as umem;
+        f(&mut part);
+        result.push(part);
+        if existing.end() > part_end {
+            let mut tail = existing;
+            tail.size = (tail.end() - part_end) as umem;
+            tail.base = part_end;
+            result.push(tail);
+        }
+    }
+    *sections = result;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(sections: &[Section]) -> Vec<(umem, umem)> {
+        sections
+            .iter()
+            .map(|section| (section.base.to_umem(), section.size))
+            .collect()
+    }
+
+    fn section(base: umem, size: umem) -> Section {
+        Section::new(base.into(), size, SectionProtection::READ_WRITE)
+    }
+
+    fn module(name: &str, base: umem, size: umem) -> ModuleInfo {
+        ModuleInfo {
+            address: Address::null(),
+            parent_process: Address::null(),
+            base: base.into(),
+            size,
+            name: name.into(),
+            path: format!("C:\\Windows\\{}", name).into(),
+            arch: ArchitectureIdent::X86(64, false),
+        }
+    }
+
+    fn map(base: umem, size: umem, page_type: PageType) -> MemoryRange {
+        CTup3(Address::from(base), size, page_type)
+    }
+
+    #[test]
+    fn merge_ranges_within_gap() {
+        let data = PageType::WRITEABLE | PageType::NOEXEC;
+        let mut sections = Vec::new();
+        merge_ranges(
+            &mut sections,
+            &[
+                map(0x1000, 0x1000, data),
+                map(0x2000, 0x1000, data),
+                map(0x4000, 0x1000, data),
+                map(0x8000, 0x1000, data),
+            ],
+            0x2000,
+        );
+        assert_eq!(ranges(&sections), vec![(0x1000, 0x4000), (0x8000, 0x1000)]);
+        assert_eq!(sections[0].protection, SectionProtection::READ_WRITE);
+    }
+
+    #[test]
+    fn merge_ranges_by_protection() {
+        let code = PageType::empty();
+        let data = PageType::WRITEABLE | PageType::NOEXEC;
+        let mut sections = vec![Section::new(
+            0x1000.into(),
+            0x1000,
+            SectionProtection::READ_EXECUTE,
+        )];
+        merge_ranges(
+            &mut sections,
+            &[
+                map(0x2000, 0x1000, code),
+                map(0x3000, 0x1000, data),
+                map(0x4000, 0x1000, code),
+            ],
+            0x1000,
+        );
+        assert_eq!(
+            ranges(&sections),
+            vec![(0x1000, 0x2000), (0x3000, 0x1000), (0x4000, 0x1000)]
+        );
+        assert_eq!(sections[0].category, SectionCategory::Code);
+        assert_eq!(sections[1].category, SectionCategory::Data);
+    }
+
+    #[test]
+    fn insert_overlapping() {
+        let mut sections = vec![section(0x1000, 0x3000), section(0x5000, 0x1000)];
+        let named = |base, size| {
+            Section::named(
+                Address::from(base),
+                size,
+                SectionType::Private,
+                SectionCategory::Heap,
+                "Heap".to_string(),
+            )
+        };
+
+        // start, middle and end of the first section
+        insert(&mut sections, named(0x1000, 0x800));
+        insert(&mut sections, named(0x2000, 0x800));
+        insert(&mut sections, named(0x3800, 0x800));
+        assert_eq!(
+            ranges(&sections),
+            vec![
+                (0x1000, 0x800),
+                (0x1800, 0x800),
+                (0x2000, 0x800),
+                (0x2800, 0x1000),
+                (0x3800, 0x800),
+                (0x5000, 0x1000),
+            ]
+        );
+        assert_eq!(sections[2].name, "Heap");
+        assert!(sections[3].name.is_empty());
+
+        // spanning the unmapped memory between two sections
+        insert(&mut sections, named(0x3c00, 0x2000));
+        assert_eq!(
+            ranges(&sections)[4..],
+            [(0x3800, 0x400), (0x3c00, 0x2000), (0x5c00, 0x400)]
+        );
+    }
+
+    #[test]
+    fn split_apply_boundaries() {
+        let mut sections = vec![section(0x1000, 0x1000), section(0x3000, 0x1000)];
+        split_apply(&mut sections, 0x1800.into(), 0x2000, |section| {
+            section.name = "split".to_string()
+        });
+        assert_eq!(
+            ranges(&sections),
+            vec![
+                (0x1000, 0x800),
+                (0x1800, 0x800),
+                (0x3000, 0x800),
+                (0x3800, 0x800)
+            ]
+        );
+        let names = sections
+            .iter()
+            .map(|section| section.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["", "split", "split", ""]);
+    }
+
+    #[test]
+    fn label_modules_overlapping() {
+        let mut sections = vec![section(0x1000, 0x8000)];
+        label_modules(
+            &mut sections,
+            &[
+                module("start.dll", 0x1000, 0x1000),
+                module("middle.dll", 0x4000, 0x1000),
+                module("adjacent.dll", 0x5000, 0x1000),
+                module("end.dll", 0x8000, 0x2000),
+            ],
+        );
+        assert_eq!(
+            ranges(&sections),
+            vec![
+                (0x1000, 0x1000),
+                (0x2000, 0x2000),
+                (0x4000, 0x1000),
+                (0x5000, 0x1000),
+                (0x6000, 0x2000),
+                (0x8000, 0x1000),
+            ]
+        );
+        let names = sections
+            .iter()
+            .map(|section| section.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec!["start.dll", "", "middle.dll", "adjacent.dll", "", "end.dll"]
+        );
+        assert_eq!(sections[0].ty, SectionType::Image);
+        assert_eq!(sections[0].module_path, r"C:\Windows\start.dll");
+        assert_eq!(sections[1].ty, SectionType::Unknown);
+        assert!(sections[1].module_path.is_empty());
+    }
+
+    #[test]
+    fn label_modules_keeps_names() {
+        let mut sections = vec![Section::named(
+            0x1000.into(),
+            0x1000,
+            SectionType::Image,
+            SectionCategory::Code,
+            ".text".to_string(),
+        )];
+        label_modules(&mut sections, &[module("ntdll.dll", 0x1000, 0x1000)]);
+        assert_eq!(sections[0].name, ".text");
+        assert_eq!(sections[0].module_path, r"C:\Windows\ntdll.dll");
+    }
+
+    #[test]
+    fn label_mappings_without_module() {
+        let mut sections = vec![section(0x1000, 0x3000)];
+        label_mappings(&mut sections, &[module("locale-archive", 0x2000, 0x1000)]);
+        assert_eq!(
+            ranges(&sections),
+            vec![(0x1000, 0x1000), (0x2000, 0x1000), (0x3000, 0x1000)]
+        );
+        assert_eq!(sections[1].name, "locale-archive");
+        assert_eq!(sections[1].ty, SectionType::Mapped);
+        assert!(sections[1].module_path.is_empty());
+    }
+}
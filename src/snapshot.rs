@@ -0,0 +1,209 @@
+use std::time::Instant;
+
+use memflow::prelude::v1::*;
+
+/// Granularity in which snapshots are read so unmapped pages do not invalidate the entire snapshot.
+const PAGE_SIZE: usize = 0x1000;
+/// Largest region that can be captured in a single snapshot.
+const MAX_SNAPSHOT_SIZE: umem = 0x1000_0000;
+
+/// A copy of a memory region at a point in time.
+pub struct Snapshot {
+    base: Address,
+    data: Vec<u8>,
+    /// Whether each page of the region could be read.
+    readable: Vec<bool>,
+    time: Instant,
+}
+
+impl Snapshot {
+    /// Captures `size` bytes at `base` page by page with the given `read` function.
+    pub fn take<F: FnMut(Address, &mut [u8]) -> bool>(
+        base: Address,
+        size: umem,
+        mut read: F,
+    ) -> Result<Self> {
+        if size == 0 || size > MAX_SNAPSHOT_SIZE {
+            return Err(Error(ErrorOrigin::Other, ErrorKind::InvalidArgument)
+                .log_warn("invalid snapshot size"));
+        }
+
+        let mut data = vec![0u8; size as usize];
+        let mut readable = Vec::new();
+        let mut offset = 0;
+        while offset < data.len() {
+            // align reads to page boundaries of the target
+            let address = base + offset;
+            let page_end = PAGE_SIZE - (address.to_umem() as usize % PAGE_SIZE);
+            let end = (offset + page_end).min(data.len());
+            readable.push(read(address, &mut data[offset..end]));
+            offset = end;
+        }
+
+        if readable.iter().all(|r| !r) {
+            return Err(Error(ErrorOrigin::Other, ErrorKind::PartialData)
+                .log_warn("unable to read snapshot region"));
+        }
+
+        Ok(Self {
+            base,
+            data,
+            readable,
+            time: Instant::now(),
+        })
+    }
+
+    pub fn base(&self) -> Address {
+        self.base
+    }
+
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn time(&self) -> Instant {
+        self.time
+    }
+
+    fn is_readable(&self, offset: usize) -> bool {
+        let page = (offset + self.base.to_umem() as usize % PAGE_SIZE) / PAGE_SIZE;
+        self.readable.get(page).copied().unwrap_or_default()
+    }
+}
+
+/// The type of a changed value guessed from the width of the change and its contents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueGuess {
+    UInt8,
+    Int16,
+    Int32,
+    Float,
+    Int64,
+    Double,
+    Pointer,
+}
+
+impl ValueGuess {
+    pub fn name(self) -> &'static str {
+        match self {
+            ValueGuess::UInt8 => "uint8",
+            ValueGuess::Int16 => "int16",
+            ValueGuess::Int32 => "int32",
+            ValueGuess::Float => "float",
+            ValueGuess::Int64 => "int64",
+            ValueGuess::Double => "double",
+            ValueGuess::Pointer => "pointer",
+        }
+    }
+
+    fn guess(width: usize, old: u64, new: u64) -> Self {
+        match width {
+            1 => ValueGuess::UInt8,
+            2 => ValueGuess::Int16,
+            4 if is_plausible_float(f32::from_bits(old as u32) as f64)
+                && is_plausible_float(f32::from_bits(new as u32) as f64) =>
+            {
+                ValueGuess::Float
+            }
+            4 => ValueGuess::Int32,
+            _ if is_plausible_pointer(old) && is_plausible_pointer(new) => ValueGuess::Pointer,
+            _ if is_plausible_float(f64::from_bits(old))
+                && is_plausible_float(f64::from_bits(new)) =>
+            {
+                ValueGuess::Double
+            }
+            _ => ValueGuess::Int64,
+        }
+    }
+
+    fn format(self, value: u64) -> String {
+        match self {
+            ValueGuess::UInt8 => (value as u8).to_string(),
+            ValueGuess::Int16 => (value as i16).to_string(),
+            ValueGuess::Int32 => (value as i32).to_string(),
+            ValueGuess::Float => f32::from_bits(value as u32).to_string(),
+            ValueGuess::Int64 => (value as i64).to_string(),
+            ValueGuess::Double => f64::from_bits(value).to_string(),
+            ValueGuess::Pointer => format!("{:x}", value),
+        }
+    }
+}
+
+fn is_plausible_float(value: f64) -> bool {
+    value == 0.0 || (value.is_normal() && value.abs() >= 1e-4 && value.abs() <= 1e7)
+}
+
+fn is_plausible_pointer(value: u64) -> bool {
+    // canonical user or kernel mode addresses above the first 64 KB
+    (0x10000..0x0000_8000_0000_0000).contains(&value) || value >= 0xFFFF_8000_0000_0000
+}
+
+/// A value which differs between two snapshots.
+#[derive(Clone, Debug)]
+pub struct Change {
+    pub offset: usize,
+    pub guess: ValueGuess,
+    pub old: u64,
+    pub new: u64,
+}
+
+impl Change {
+    pub fn old_value(&self) -> String {
+        self.guess.format(self.old)
+    }
+
+    pub fn new_value(&self) -> String {
+        self.guess.format(self.new)
+    }
+}
+
+/// Lists all values which changed between two snapshots of the same region.
+///
+/// The region is compared in aligned 8 byte blocks. Each changed block is narrowed down
+/// to the smallest aligned 1, 2, 4 or 8 byte value covering all changed bytes
+/// which determines the guessed type of the value.
+/// Pages which could not be read in either snapshot are skipped.
+pub fn diff(old: &Snapshot, new: &Snapshot) -> Result<Vec<Change>> {
+    if old.base != new.base || old.size() != new.size() {
+        return Err(Error(ErrorOrigin::Other, ErrorKind::InvalidArgument)
+            .log_warn("snapshots cover different regions"));
+    }
+
+    let mut changes = Vec::new();
+    for block in (0..old.size()).step_by(8) {
+        let end = (block + 8).min(old.size());
+        if !old.is_readable(block) || !new.is_readable(block) {
+            continue;
+        }
+
+        let changed = (block..end)
+            .filter(|&i| old.data[i] != new.data[i])
+            .collect::<Vec<_>>();
+        let (first, last) = match (changed.first(), changed.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => continue,
+        };
+
+        let width = [1, 2, 4, 8]
+            .iter()
+            .copied()
+            .find(|width| first / width == last / width)
+            .unwrap_or(8);
+        let offset = first - first % width;
+        let end = (offset + width).min(old.size());
+
+        let value = |data: &[u8]| {
+            let mut buf = [0u8; 8];
+            buf[..end - offset].copy_from_slice(&data[offset..end]);
+            u64::from_le_bytes(buf)
+        };
+        let (old_value, new_value) = (value(&old.data), value(&new.data));
+        changes.push(Change {
+            offset,
+            guess: ValueGuess::guess(width, old_value, new_value),
+            old: old_value,
+            new: new_value,
+        });
+    }
+    Ok(changes)
+}
@@ -0,0 +1,133 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Counters of the memory accesses and enumerations performed for ReClass.NET.
+///
+/// The counters only grow, rates are computed from the difference of two snapshots.
+struct Counters {
+    reads: AtomicU64,
+    failed_reads: AtomicU64,
+    read_nanos: AtomicU64,
+    writes: AtomicU64,
+    failed_writes: AtomicU64,
+    enumerations: AtomicU64,
+    enumeration_nanos: AtomicU64,
+    section_cache_hits: AtomicU64,
+    section_cache_misses: AtomicU64,
+}
+
+static COUNTERS: Counters = Counters {
+    reads: AtomicU64::new(0),
+    failed_reads: AtomicU64::new(0),
+    read_nanos: AtomicU64::new(0),
+    writes: AtomicU64::new(0),
+    failed_writes: AtomicU64::new(0),
+    enumerations: AtomicU64::new(0),
+    enumeration_nanos: AtomicU64::new(0),
+    section_cache_hits: AtomicU64::new(0),
+    section_cache_misses: AtomicU64::new(0),
+};
+
+/// The values of all counters at one point in time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Snapshot {
+    pub reads: u64,
+    pub failed_reads: u64,
+    pub read_nanos: u64,
+    pub writes: u64,
+    pub failed_writes: u64,
+    pub enumerations: u64,
+    pub enumeration_nanos: u64,
+    pub section_cache_hits: u64,
+    pub section_cache_misses: u64,
+}
+
+impl Snapshot {
+    /// Returns the counters which were added since `earlier` was taken.
+    pub fn since(&self, earlier: &Snapshot) -> Snapshot {
+        Snapshot {
+            reads: self.reads - earlier.reads,
+            failed_reads: self.failed_reads - earlier.failed_reads,
+            read_nanos: self.read_nanos - earlier.read_nanos,
+            writes: self.writes - earlier.writes,
+            failed_writes: self.failed_writes - earlier.failed_writes,
+            enumerations: self.enumerations - earlier.enumerations,
+            enumeration_nanos: self.enumeration_nanos - earlier.enumeration_nanos,
+            section_cache_hits: self.section_cache_hits - earlier.section_cache_hits,
+            section_cache_misses: self.section_cache_misses - earlier.section_cache_misses,
+        }
+    }
+
+    /// Average duration of a read, `None` if nothing has been read.
+    pub fn read_latency(&self) -> Option<Duration> {
+        self.read_nanos
+            .checked_div(self.reads)
+            .map(Duration::from_nanos)
+    }
+
+    /// Average duration of an enumeration, `None` if nothing has been enumerated.
+    pub fn enumeration_latency(&self) -> Option<Duration> {
+        self.enumeration_nanos
+            .checked_div(self.enumerations)
+            .map(Duration::from_nanos)
+    }
+
+    /// Share of section cache lookups which were answered from the cache, `None` without lookups.
+    pub fn section_cache_hit_ratio(&self) -> Option<f64> {
+        let lookups = self.section_cache_hits + self.section_cache_misses;
+        if lookups == 0 {
+            None
+        } else {
+            Some(self.section_cache_hits as f64 / lookups as f64)
+        }
+    }
+}
+
+pub fn record_read(success: bool, elapsed: Duration) {
+    COUNTERS.reads.fetch_add(1, Ordering::Relaxed);
+    if !success {
+        COUNTERS.failed_reads.fetch_add(1, Ordering::Relaxed);
+    }
+    COUNTERS
+        .read_nanos
+        .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+}
+
+pub fn record_write(success: bool) {
+    COUNTERS.writes.fetch_add(1, Ordering::Relaxed);
+    if !success {
+        COUNTERS.failed_writes.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+pub fn record_enumeration(elapsed: Duration) {
+    COUNTERS.enumerations.fetch_add(1, Ordering::Relaxed);
+    COUNTERS
+        .enumeration_nanos
+        .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+}
+
+/// Records a lookup of the section cache.
+pub fn record_section_cache_lookup(hit: bool) {
+    if hit {
+        COUNTERS.section_cache_hits.fetch_add(1, Ordering::Relaxed);
+    } else {
+        COUNTERS
+            .section_cache_misses
+            .fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+pub fn snapshot() -> Snapshot {
+    Snapshot {
+        reads: COUNTERS.reads.load(Ordering::Relaxed),
+        failed_reads: COUNTERS.failed_reads.load(Ordering::Relaxed),
+        read_nanos: COUNTERS.read_nanos.load(Ordering::Relaxed),
+        writes: COUNTERS.writes.load(Ordering::Relaxed),
+        failed_writes: COUNTERS.failed_writes.load(Ordering::Relaxed),
+        enumerations: COUNTERS.enumerations.load(Ordering::Relaxed),
+        enumeration_nanos: COUNTERS.enumeration_nanos.load(Ordering::Relaxed),
+        section_cache_hits: COUNTERS.section_cache_hits.load(Ordering::Relaxed),
+        section_cache_misses: COUNTERS.section_cache_misses.load(Ordering::Relaxed),
+    }
+}
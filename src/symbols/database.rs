@@ -0,0 +1,362 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::path::Path;
+
+use memflow::prelude::v1::umem;
+use pdb::{FallibleIterator, RawString, SymbolData, TypeData, TypeFinder, TypeIndex, PDB};
+
+use super::pe::PdbId;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrimitiveType {
+    Void,
+    Bool,
+    Char,
+    WideChar,
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Int64,
+    UInt64,
+    Float,
+    Double,
+    Other(u64),
+}
+
+impl PrimitiveType {
+    fn from_kind(kind: pdb::PrimitiveKind) -> Self {
+        use pdb::PrimitiveKind::*;
+        match kind {
+            NoType | Void => PrimitiveType::Void,
+            Bool8 => PrimitiveType::Bool,
+            Char | RChar => PrimitiveType::Char,
+            WChar | RChar16 => PrimitiveType::WideChar,
+            I8 => PrimitiveType::Int8,
+            UChar | U8 => PrimitiveType::UInt8,
+            Short | I16 => PrimitiveType::Int16,
+            UShort | U16 | Bool16 => PrimitiveType::UInt16,
+            Long | I32 | HRESULT => PrimitiveType::Int32,
+            ULong | U32 | RChar32 | Bool32 => PrimitiveType::UInt32,
+            Quad | I64 => PrimitiveType::Int64,
+            UQuad | U64 | Bool64 => PrimitiveType::UInt64,
+            F32 | F32PP => PrimitiveType::Float,
+            F64 => PrimitiveType::Double,
+            F16 => PrimitiveType::Other(2),
+            F48 => PrimitiveType::Other(6),
+            F80 => PrimitiveType::Other(10),
+            _ => PrimitiveType::Other(16),
+        }
+    }
+
+    pub fn size(self) -> u64 {
+        match self {
+            PrimitiveType::Void => 0,
+            PrimitiveType::Bool | PrimitiveType::Char => 1,
+            PrimitiveType::Int8 | PrimitiveType::UInt8 => 1,
+            PrimitiveType::WideChar | PrimitiveType::Int16 | PrimitiveType::UInt16 => 2,
+            PrimitiveType::Int32 | PrimitiveType::UInt32 | PrimitiveType::Float => 4,
+            PrimitiveType::Int64 | PrimitiveType::UInt64 | PrimitiveType::Double => 8,
+            PrimitiveType::Other(size) => size,
+        }
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            PrimitiveType::Void => "void".to_string(),
+            PrimitiveType::Bool => "bool".to_string(),
+            PrimitiveType::Char => "char".to_string(),
+            PrimitiveType::WideChar => "wchar_t".to_string(),
+            PrimitiveType::Int8 => "int8_t".to_string(),
+            PrimitiveType::UInt8 => "uint8_t".to_string(),
+            PrimitiveType::Int16 => "int16_t".to_string(),
+            PrimitiveType::UInt16 => "uint16_t".to_string(),
+            PrimitiveType::Int32 => "int32_t".to_string(),
+            PrimitiveType::UInt32 => "uint32_t".to_string(),
+            PrimitiveType::Int64 => "int64_t".to_string(),
+            PrimitiveType::UInt64 => "uint64_t".to_string(),
+            PrimitiveType::Float => "float".to_string(),
+            PrimitiveType::Double => "double".to_string(),
+            PrimitiveType::Other(size) => format!("uint8_t[{}]", size),
+        }
+    }
+}
+
+/// The type of a single struct member.
+#[derive(Clone, Debug)]
+pub enum FieldType {
+    Primitive(PrimitiveType),
+    Pointer(Box<FieldType>, u64),
+    Array(Box<FieldType>, u64),
+    Struct(String, u64),
+    Enum(String, PrimitiveType),
+    Bitfield(Box<FieldType>, u8, u8),
+    Function,
+}
+
+impl FieldType {
+    pub fn size(&self) -> u64 {
+        match self {
+            FieldType::Primitive(primitive) => primitive.size(),
+            FieldType::Pointer(_, size) => *size,
+            FieldType::Array(element, count) => element.size() * count,
+            FieldType::Struct(_, size) => *size,
+            FieldType::Enum(_, underlying) => underlying.size(),
+            FieldType::Bitfield(underlying, _, _) => underlying.size(),
+            FieldType::Function => 0,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            FieldType::Primitive(primitive) => primitive.name(),
+            FieldType::Pointer(target, _) => format!("{}*", target.name()),
+            FieldType::Array(element, count) => format!("{}[{}]", element.name(), count),
+            FieldType::Struct(name, _) | FieldType::Enum(name, _) => name.clone(),
+            FieldType::Bitfield(underlying, _, length) => {
+                format!("{}:{}", underlying.name(), length)
+            }
+            FieldType::Function => "function".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TypeField {
+    pub name: String,
+    pub offset: u64,
+    pub ty: FieldType,
+}
+
+/// Memory layout of a struct, class or union.
+#[derive(Clone, Debug)]
+pub struct TypeLayout {
+    pub size: u64,
+    pub fields: Vec<TypeField>,
+}
+
+/// Reasons why a PDB could not be loaded.
+#[derive(Debug)]
+pub enum DatabaseError {
+    /// The file could not be read or parsed.
+    Pdb(pdb::Error),
+    /// The PDB belongs to a different build of the module.
+    GuidMismatch,
+    /// The PDB belongs to a different link of the module.
+    AgeMismatch,
+}
+
+impl fmt::Display for DatabaseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DatabaseError::Pdb(err) => write!(f, "{}", err),
+            DatabaseError::GuidMismatch => write!(f, "pdb guid mismatch"),
+            DatabaseError::AgeMismatch => write!(f, "pdb age mismatch"),
+        }
+    }
+}
+
+impl From<pdb::Error> for DatabaseError {
+    fn from(err: pdb::Error) -> Self {
+        DatabaseError::Pdb(err)
+    }
+}
+
+/// Public symbols and type layouts loaded from a PDB file.
+pub struct ProgramDatabase {
+    pub symbols: Vec<(umem, String)>,
+    pub types: HashMap<String, TypeLayout>,
+}
+
+impl ProgramDatabase {
+    /// Loads the PDB at `path` and verifies that it matches the given `id`.
+    pub fn open(path: &Path, id: &PdbId) -> Result<Self, DatabaseError> {
+        let mut pdb = PDB::open(File::open(path).map_err(pdb::Error::from)?)?;
+
+        let info = pdb.pdb_information()?;
+        let (data1, data2, data3, data4) = id.guid_fields();
+        if info.guid.as_fields() != (data1, data2, data3, &data4) {
+            return Err(DatabaseError::GuidMismatch);
+        }
+
+        // the age written by the linker has to match the image exactly, the age of the
+        // information stream may be bumped by other tools and is only checked for old PDBs
+        let age_matches = match pdb.debug_information()?.age() {
+            Some(age) => age == id.age,
+            None => info.age >= id.age,
+        };
+        if !age_matches {
+            return Err(DatabaseError::AgeMismatch);
+        }
+
+        let symbols = Self::parse_symbols(&mut pdb)?;
+        let types = Self::parse_types(&mut pdb)?;
+        Ok(Self { symbols, types })
+    }
+
+    fn parse_symbols(pdb: &mut PDB<File>) -> pdb::Result<Vec<(umem, String)>> {
+        let address_map = pdb.address_map()?;
+        let symbol_table = pdb.global_symbols()?;
+
+        let mut symbols = Vec::new();
+        let mut iter = symbol_table.iter();
+        while let Some(symbol) = iter.next()? {
+            if let Ok(SymbolData::Public(data)) = symbol.parse() {
+                if let Some(rva) = data.offset.to_rva(&address_map) {
+                    symbols.push((rva.0 as umem, data.name.to_string().into_owned()));
+                }
+            }
+        }
+        Ok(symbols)
+    }
+
+    fn parse_types(pdb: &mut PDB<File>) -> pdb::Result<HashMap<String, TypeLayout>> {
+        let type_information = pdb.type_information()?;
+        let mut finder = type_information.finder();
+
+        // the first pass records the sizes of all complete types
+        // so members referencing forward declarations can be sized properly
+        let mut sizes = HashMap::new();
+        let mut complete = Vec::new();
+        let mut iter = type_information.iter();
+        while let Some(item) = iter.next()? {
+            finder.update(&iter);
+            match item.parse() {
+                Ok(TypeData::Class(class)) if !class.properties.forward_reference() => {
+                    let name = type_key(class.name, class.unique_name);
+                    sizes.insert(name.clone(), class.size);
+                    if let Some(fields) = class.fields {
+                        complete.push((name, class.size, fields));
+                    }
+                }
+                Ok(TypeData::Union(union)) if !union.properties.forward_reference() => {
+                    let name = type_key(union.name, union.unique_name);
+                    sizes.insert(name.clone(), union.size);
+                    complete.push((name, union.size, union.fields));
+                }
+                _ => {}
+            }
+        }
+
+        let mut types = HashMap::new();
+        for (name, size, fields) in complete.into_iter() {
+            let mut layout = TypeLayout {
+                size,
+                fields: Vec::new(),
+            };
+            collect_fields(&finder, &sizes, fields, &mut layout.fields);
+            layout.fields.sort_by_key(|f| f.offset);
+            types.insert(name, layout);
+        }
+        Ok(types)
+    }
+}
+
+/// Returns the name under which a type is stored.
+///
+/// Anonymous types all share the same name and are identified by their unique name instead.
+fn type_key(name: RawString, unique_name: Option<RawString>) -> String {
+    let name = name.to_string();
+    match unique_name {
+        Some(unique_name) if name.starts_with("<unnamed-") || name.starts_with("<anonymous-") => {
+            unique_name.to_string().into_owned()
+        }
+        _ => name.into_owned(),
+    }
+}
+
+fn collect_fields(
+    finder: &TypeFinder,
+    sizes: &HashMap<String, u64>,
+    index: TypeIndex,
+    out: &mut Vec<TypeField>,
+) {
+    let mut next = Some(index);
+    while let Some(index) = next.take() {
+        if let Ok(TypeData::FieldList(list)) = finder.find(index).and_then(|item| item.parse()) {
+            for field in list.fields.iter() {
+                if let TypeData::Member(member) = field {
+                    out.push(TypeField {
+                        name: member.name.to_string().into_owned(),
+                        offset: member.offset,
+                        ty: resolve_type(finder, sizes, member.field_type),
+                    });
+                }
+            }
+            next = list.continuation;
+        }
+    }
+}
+
+fn resolve_type(finder: &TypeFinder, sizes: &HashMap<String, u64>, index: TypeIndex) -> FieldType {
+    let data = match finder.find(index).and_then(|item| item.parse()) {
+        Ok(data) => data,
+        Err(_) => return FieldType::Primitive(PrimitiveType::Void),
+    };
+
+    match data {
+        TypeData::Primitive(primitive) => match primitive.indirection {
+            Some(indirection) => {
+                let size = match indirection {
+                    pdb::Indirection::Near64 => 8,
+                    pdb::Indirection::Near128 => 16,
+                    pdb::Indirection::Near16 => 2,
+                    _ => 4,
+                };
+                FieldType::Pointer(
+                    Box::new(FieldType::Primitive(PrimitiveType::from_kind(
+                        primitive.kind,
+                    ))),
+                    size,
+                )
+            }
+            None => FieldType::Primitive(PrimitiveType::from_kind(primitive.kind)),
+        },
+        TypeData::Pointer(pointer) => FieldType::Pointer(
+            Box::new(resolve_type(finder, sizes, pointer.underlying_type)),
+            pointer.attributes.size() as u64,
+        ),
+        TypeData::Modifier(modifier) => resolve_type(finder, sizes, modifier.underlying_type),
+        TypeData::Array(array) => {
+            let element = resolve_type(finder, sizes, array.element_type);
+            let total = array.dimensions.last().copied().unwrap_or_default() as u64;
+            let count = if element.size() > 0 {
+                total / element.size()
+            } else {
+                0
+            };
+            FieldType::Array(Box::new(element), count)
+        }
+        TypeData::Class(class) => {
+            let name = type_key(class.name, class.unique_name);
+            let size = sizes.get(&name).copied().unwrap_or(class.size);
+            FieldType::Struct(name, size)
+        }
+        TypeData::Union(union) => {
+            let name = type_key(union.name, union.unique_name);
+            let size = sizes.get(&name).copied().unwrap_or(union.size);
+            FieldType::Struct(name, size)
+        }
+        TypeData::Enumeration(enumeration) => {
+            match resolve_type(finder, sizes, enumeration.underlying_type) {
+                FieldType::Primitive(underlying) => {
+                    FieldType::Enum(enumeration.name.to_string().into_owned(), underlying)
+                }
+                _ => FieldType::Enum(
+                    enumeration.name.to_string().into_owned(),
+                    PrimitiveType::Int32,
+                ),
+            }
+        }
+        TypeData::Bitfield(bitfield) => FieldType::Bitfield(
+            Box::new(resolve_type(finder, sizes, bitfield.underlying_type)),
+            bitfield.position,
+            bitfield.length,
+        ),
+        TypeData::Procedure(_) | TypeData::MemberFunction(_) => FieldType::Function,
+        _ => FieldType::Primitive(PrimitiveType::Void),
+    }
+}
@@ -0,0 +1,244 @@
+pub mod database;
+pub mod pe;
+pub mod store;
+
+use std::sync::Arc;
+
+use memflow::prelude::v1::*;
+
+use database::{FieldType, ProgramDatabase, TypeLayout};
+
+/// Export and PDB symbols of a single module, sorted by their offset.
+pub struct ModuleSymbols {
+    base: Address,
+    size: umem,
+    name: String,
+    path: String,
+    exports: Vec<(umem, String)>,
+    database: Option<Arc<ProgramDatabase>>,
+}
+
+impl ModuleSymbols {
+    pub fn new(
+        module: &ModuleInfo,
+        exports: Vec<ExportInfo>,
+        database: Option<Arc<ProgramDatabase>>,
+    ) -> Self {
+        let mut exports = exports
+            .into_iter()
+            .map(|e| (e.offset, e.name.to_string()))
+            .collect::<Vec<_>>();
+        if let Some(database) = database.as_ref() {
+            exports.extend(database.symbols.iter().cloned());
+        }
+        exports.sort();
+        exports.dedup();
+
+        Self {
+            base: module.base,
+            size: module.size,
+            name: module.name.to_string(),
+            path: module.path.to_string(),
+            exports,
+            database,
+        }
+    }
+
+    /// Returns all symbols of this module as absolute addresses.
+    pub fn symbols(&self) -> impl Iterator<Item = (Address, &str)> {
+        self.exports
+            .iter()
+            .map(move |(offset, name)| (self.base + *offset, name.as_str()))
+    }
+
+    /// Returns the PDB loaded for this module.
+    pub fn database(&self) -> Option<&ProgramDatabase> {
+        self.database.as_deref()
+    }
+
+    /// Checks if the module matches the given name, path or file stem.
+    fn matches(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+            || self.path.eq_ignore_ascii_case(name)
+            || self
+                .name
+                .rsplit_once('.')
+                .map(|(stem, _)| stem.eq_ignore_ascii_case(name))
+                .unwrap_or_default()
+    }
+
+    fn contains(&self, address: Address) -> bool {
+        address >= self.base && address.to_umem() < self.base.to_umem() + self.size
+    }
+
+    /// Returns the closest export at or below the given offset.
+    fn export_by_offset(&self, offset: umem) -> Option<&(umem, String)> {
+        let idx = self.exports.partition_point(|e| e.0 <= offset);
+        if idx > 0 {
+            self.exports.get(idx - 1)
+        } else {
+            None
+        }
+    }
+
+    fn export_by_name(&self, name: &str) -> Option<umem> {
+        self.exports.iter().find(|e| e.1 == name).map(|e| e.0)
+    }
+}
+
+/// Caches the export symbols of all modules of a process.
+#[derive(Default)]
+pub struct SymbolCache {
+    modules: Vec<ModuleSymbols>,
+}
+
+impl SymbolCache {
+    /// Evicts the modules which are no longer loaded and returns the modules
+    /// of `modules` whose symbols have not been loaded yet.
+    pub fn missing(&mut self, modules: &[ModuleInfo]) -> Vec<ModuleInfo> {
+        self.modules.retain(|m| {
+            modules
+                .iter()
+                .any(|n| m.base == n.base && m.size == n.size && m.path == n.path.as_ref())
+        });
+
+        modules
+            .iter()
+            .filter(|module| !self.modules.iter().any(|m| m.base == module.base))
+            .cloned()
+            .collect()
+    }
+
+    /// Evicts the modules matching `name` for which no PDB has been loaded,
+    /// so their symbols are loaded again by the next update.
+    pub fn evict_without_database(&mut self, name: &str) {
+        self.modules
+            .retain(|m| m.database.is_some() || !m.matches(name));
+    }
+
+    /// Adds the symbols of newly loaded modules, modules which are already cached are skipped.
+    pub fn insert(&mut self, symbols: Vec<ModuleSymbols>) {
+        for module in symbols {
+            if !self.modules.iter().any(|m| m.base == module.base) {
+                self.modules.push(module);
+            }
+        }
+
+        self.modules.sort_by_key(|m| m.base);
+    }
+
+    /// Resolves an address to a `module!symbol+offset` string.
+    ///
+    /// If the address lies inside of a module but no export precedes it
+    /// the address is resolved to `module+offset` instead.
+    pub fn symbol_by_address(&self, address: Address) -> Option<String> {
+        let idx = self.modules.partition_point(|m| m.base <= address);
+        let module = self.modules.get(idx.checked_sub(1)?)?;
+        if !module.contains(address) {
+            return None;
+        }
+
+        let offset = address.to_umem() - module.base.to_umem();
+        Some(match module.export_by_offset(offset) {
+            Some((export_offset, export_name)) if offset == *export_offset => {
+                format!("{}!{}", module.name, export_name)
+            }
+            Some((export_offset, export_name)) => format!(
+                "{}!{}+{:#x}",
+                module.name,
+                export_name,
+                offset - export_offset
+            ),
+            None => format!("{}+{:#x}", module.name, offset),
+        })
+    }
+
+    /// Resolves a `module!symbol+offset`, `module!symbol` or `module+offset` string to an address.
+    pub fn address_by_symbol(&self, symbol: &str) -> Option<Address> {
+        let (symbol, offset) = match symbol.rfind('+') {
+            Some(idx) => {
+                let offset = symbol[idx + 1..].trim();
+                let offset = match offset.strip_prefix("0x") {
+                    Some(hex) => umem::from_str_radix(hex, 16).ok()?,
+                    None => umem::from_str_radix(offset, 16).ok()?,
+                };
+                (symbol[..idx].trim(), offset)
+            }
+            None => (symbol.trim(), 0),
+        };
+
+        let (module_name, export_name) = match symbol.find('!') {
+            Some(idx) => (&symbol[..idx], Some(&symbol[idx + 1..])),
+            None => (symbol, None),
+        };
+
+        let module = self.module_by_name(module_name)?;
+
+        let export_offset = match export_name {
+            Some(name) => module.export_by_name(name)?,
+            None => 0,
+        };
+
+        Some(module.base + export_offset + offset)
+    }
+
+    pub fn module_by_name(&self, name: &str) -> Option<&ModuleSymbols> {
+        self.modules.iter().find(|m| m.matches(name))
+    }
+
+    /// Looks up a type layout by its `module!type` or plain `type` name.
+    ///
+    /// Plain type names are searched for in the PDBs of all modules.
+    pub fn type_layout(&self, name: &str) -> Option<&TypeLayout> {
+        self.type_database(name)
+            .and_then(|(database, name)| database.types.get(name))
+    }
+
+    /// Returns the offset of a (nested) field like `Tcb.Teb` in a type.
+    pub fn field_offset(&self, type_name: &str, path: &str) -> Option<u64> {
+        self.field(type_name, path).map(|(offset, _)| offset)
+    }
+
+    /// Returns the offset and type of a (nested) field like `Tcb.Teb` in a type.
+    pub fn field(&self, type_name: &str, path: &str) -> Option<(u64, &FieldType)> {
+        let (database, name) = self.type_database(type_name)?;
+        let mut layout = database.types.get(name)?;
+        let mut offset = 0;
+        let mut fields = path.split('.').peekable();
+        while let Some(field_name) = fields.next() {
+            let field = layout.fields.iter().find(|f| f.name == field_name)?;
+            offset += field.offset;
+            if fields.peek().is_none() {
+                return Some((offset, &field.ty));
+            }
+            layout = match &field.ty {
+                FieldType::Struct(name, _) => database.types.get(name)?,
+                _ => return None,
+            };
+        }
+        None
+    }
+
+    /// Looks up the PDB containing a type by its `module!type` or plain `type` name.
+    ///
+    /// Returns the database together with the unqualified type name.
+    pub fn type_database<'a>(&self, name: &'a str) -> Option<(&ProgramDatabase, &'a str)> {
+        match name.find('!') {
+            Some(idx) => {
+                let database = self.module_by_name(&name[..idx])?.database()?;
+                let name = &name[idx + 1..];
+                if database.types.contains_key(name) {
+                    Some((database, name))
+                } else {
+                    None
+                }
+            }
+            None => self
+                .modules
+                .iter()
+                .filter_map(|m| m.database())
+                .find(|database| database.types.contains_key(name))
+                .map(|database| (database, name)),
+        }
+    }
+}
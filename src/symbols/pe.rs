@@ -0,0 +1,121 @@
+use memflow::prelude::v1::*;
+
+const IMAGE_DIRECTORY_ENTRY_DEBUG: usize = 6;
+const IMAGE_DEBUG_TYPE_CODEVIEW: u32 = 2;
+
+/// Identifies the PDB belonging to a module via its CodeView debug record.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PdbId {
+    pub name: String,
+    pub guid: [u8; 16],
+    pub age: u32,
+}
+
+impl PdbId {
+    /// Returns the `Data1`, `Data2`, `Data3` and `Data4` fields of the GUID.
+    pub fn guid_fields(&self) -> (u32, u16, u16, [u8; 8]) {
+        let mut data4 = [0u8; 8];
+        data4.copy_from_slice(&self.guid[8..]);
+        (
+            u32::from_le_bytes([self.guid[0], self.guid[1], self.guid[2], self.guid[3]]),
+            u16::from_le_bytes([self.guid[4], self.guid[5]]),
+            u16::from_le_bytes([self.guid[6], self.guid[7]]),
+            data4,
+        )
+    }
+
+    /// Returns the `{GUID}{AGE}` directory name used by symbol stores.
+    pub fn signature(&self) -> String {
+        let (data1, data2, data3, data4) = self.guid_fields();
+        let data4 = data4
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<String>();
+        format!(
+            "{:08X}{:04X}{:04X}{}{:X}",
+            data1, data2, data3, data4, self.age
+        )
+    }
+}
+
+fn read_u16(buf: &[u8], offset: usize) -> Option<u16> {
+    buf.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_u32(buf: &[u8], offset: usize) -> Option<u32> {
+    buf.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn invalid_exe() -> Error {
+    Error(ErrorOrigin::OsLayer, ErrorKind::InvalidExeFile)
+}
+
+/// Reads the CodeView debug record from the PE headers of the module at `base`.
+pub fn read_pdb_id<T: MemoryView>(mem: &mut T, base: Address) -> Result<PdbId> {
+    let header = mem.read_raw(base, size::kb(4)).data_part()?;
+    if header.get(0..2) != Some(b"MZ") {
+        return Err(invalid_exe());
+    }
+
+    let nt_offset = read_u32(&header, 0x3c).ok_or_else(invalid_exe)? as usize;
+    if header.get(nt_offset..nt_offset + 4) != Some(b"PE\0\0") {
+        return Err(invalid_exe());
+    }
+
+    // the optional header directly follows the 20 byte file header
+    let optional_offset = nt_offset + 24;
+    let data_directory_offset = match read_u16(&header, optional_offset) {
+        Some(0x10b) => optional_offset + 96,
+        Some(0x20b) => optional_offset + 112,
+        _ => return Err(invalid_exe()),
+    };
+
+    let debug_offset = data_directory_offset + IMAGE_DIRECTORY_ENTRY_DEBUG * 8;
+    let debug_rva = read_u32(&header, debug_offset).ok_or_else(invalid_exe)?;
+    let debug_size = read_u32(&header, debug_offset + 4).ok_or_else(invalid_exe)?;
+    if debug_rva == 0 || debug_size == 0 {
+        return Err(Error(ErrorOrigin::OsLayer, ErrorKind::NotFound));
+    }
+
+    // each IMAGE_DEBUG_DIRECTORY entry is 28 bytes long
+    let debug_dir = mem
+        .read_raw(base + debug_rva as umem, (debug_size as usize).min(28 * 32))
+        .data_part()?;
+    for entry in debug_dir.chunks_exact(28) {
+        if read_u32(entry, 12) != Some(IMAGE_DEBUG_TYPE_CODEVIEW) {
+            continue;
+        }
+
+        let data_size = read_u32(entry, 16).unwrap_or_default() as usize;
+        let data_rva = read_u32(entry, 20).unwrap_or_default();
+        if data_rva == 0 || data_size < 24 {
+            continue;
+        }
+
+        let data = mem
+            .read_raw(base + data_rva as umem, data_size.min(size::kb(1)))
+            .data_part()?;
+        if data.get(0..4) != Some(b"RSDS") {
+            continue;
+        }
+
+        let mut guid = [0u8; 16];
+        guid.copy_from_slice(&data[4..20]);
+        let age = read_u32(&data, 20).ok_or_else(invalid_exe)?;
+
+        let path = &data[24..];
+        let path = &path[..path.iter().position(|&c| c == 0).unwrap_or(path.len())];
+        let path = String::from_utf8_lossy(path);
+        let name = path
+            .rsplit(['\\', '/'])
+            .next()
+            .unwrap_or_default()
+            .to_string();
+
+        return Ok(PdbId { name, guid, age });
+    }
+
+    Err(Error(ErrorOrigin::OsLayer, ErrorKind::NotFound))
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use memflow::prelude::v1::*;
+
+use super::database::ProgramDatabase;
+use super::pe::{self, PdbId};
+
+/// A local symbol store with the standard `<pdb>/<GUID><AGE>/<pdb>` layout.
+///
+/// Loaded databases are shared between all handles so a PDB is only parsed once.
+#[derive(Default)]
+pub struct SymbolStore {
+    path: Option<PathBuf>,
+    databases: HashMap<PdbId, Option<Arc<ProgramDatabase>>>,
+}
+
+impl SymbolStore {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: if !path.is_empty() {
+                Some(PathBuf::from(path))
+            } else {
+                None
+            },
+            databases: HashMap::new(),
+        }
+    }
+
+    /// Returns the location of the PDB matching `id` in the store.
+    pub fn pdb_path(&self, id: &PdbId) -> Option<PathBuf> {
+        self.path
+            .as_ref()
+            .map(|path| path.join(&id.name).join(id.signature()).join(&id.name))
+    }
+
+    /// Loads the PDB matching `id` from the store.
+    ///
+    /// PDBs which failed to load are remembered so they are not parsed again,
+    /// missing PDBs are searched for again as they might be added to the store later.
+    pub fn load(&mut self, id: &PdbId) -> Option<Arc<ProgramDatabase>> {
+        if let Some(database) = self.databases.get(id) {
+            return database.clone();
+        }
+
+        let path = self.pdb_path(id)?;
+        let database = if path.exists() {
+            match ProgramDatabase::open(&path, id) {
+                Ok(database) => {
+                    log::info!(
+                        "loaded {} ({} symbols, {} types)",
+                        path.display(),
+                        database.symbols.len(),
+                        database.types.len()
+                    );
+                    Some(Arc::new(database))
+                }
+                Err(err) => {
+                    log::warn!("unable to load {}: {}", path.display(), err);
+                    None
+                }
+            }
+        } else {
+            log::debug!("{} not found in symbol store", path.display());
+            return None;
+        };
+
+        self.databases.insert(id.clone(), database.clone());
+        database
+    }
+
+    /// Reads the debug directory of `module` and loads the matching PDB from the store.
+    pub fn load_module<T: MemoryView>(
+        &mut self,
+        mem: &mut T,
+        module: &ModuleInfo,
+    ) -> Option<Arc<ProgramDatabase>> {
+        // avoid reading module headers if no store is configured
+        self.path.as_ref()?;
+
+        match pe::read_pdb_id(mem, module.base) {
+            Ok(id) => self.load(&id),
+            Err(err) => {
+                log::debug!("unable to read pdb info of {}: {}", module.name, err);
+                None
+            }
+        }
+    }
+}
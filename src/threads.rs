@@ -0,0 +1,120 @@
+use memflow::prelude::v1::*;
+
+use crate::symbols::SymbolCache;
+
+/// Upper bound of threads walked per process to protect against corrupted lists.
+const MAX_THREADS: usize = 0x10000;
+
+/// Offsets into the kernel structures required to enumerate the threads of a process.
+///
+/// The offsets change between Windows builds and are taken from the ntoskrnl PDB.
+pub struct ThreadOffsets {
+    /// `_EPROCESS.ThreadListHead`
+    thread_list_head: umem,
+    /// `_ETHREAD.ThreadListEntry`
+    thread_list_entry: umem,
+    /// `_ETHREAD.Cid.UniqueThread`
+    thread_id: umem,
+    /// `_ETHREAD.Tcb.Teb`
+    teb: umem,
+    /// `_TEB.DeallocationStack`, the start of the reserved user stack
+    deallocation_stack: Option<umem>,
+    /// size of `_TEB`
+    teb_size: umem,
+}
+
+impl ThreadOffsets {
+    pub fn new(symbols: &SymbolCache) -> Option<Self> {
+        Some(Self {
+            thread_list_head: symbols.field_offset("_EPROCESS", "ThreadListHead")?,
+            thread_list_entry: symbols.field_offset("_ETHREAD", "ThreadListEntry")?,
+            thread_id: symbols.field_offset("_ETHREAD", "Cid.UniqueThread")?,
+            teb: symbols.field_offset("_ETHREAD", "Tcb.Teb")?,
+            deallocation_stack: symbols.field_offset("_TEB", "DeallocationStack"),
+            teb_size: symbols
+                .type_layout("_TEB")
+                .map(|layout| layout.size)
+                .unwrap_or(0x1000),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ThreadInfo {
+    pub id: u32,
+    pub teb: Address,
+    pub teb_size: umem,
+    /// The user mode stack of the thread as `(limit, base)`, the stack grows down from `base`.
+    pub stack: Option<(Address, Address)>,
+}
+
+/// Walks the thread list of the process at `eprocess` in kernel memory.
+///
+/// Returns the id and TEB address of each thread.
+pub fn enumerate<T: MemoryView>(
+    kernel: &mut T,
+    arch: ArchitectureObj,
+    offsets: &ThreadOffsets,
+    eprocess: Address,
+) -> Vec<(u32, Address)> {
+    let head = eprocess + offsets.thread_list_head;
+    let mut threads = Vec::new();
+    let mut entry = kernel
+        .read_addr_arch(arch, head)
+        .data_part()
+        .unwrap_or_default();
+    while !entry.is_null() && entry != head && threads.len() < MAX_THREADS {
+        let ethread = entry - offsets.thread_list_entry;
+        let id = kernel
+            .read_addr_arch(arch, ethread + offsets.thread_id)
+            .data_part()
+            .unwrap_or_default();
+        let teb = kernel
+            .read_addr_arch(arch, ethread + offsets.teb)
+            .data_part()
+            .unwrap_or_default();
+        threads.push((id.to_umem() as u32, teb));
+
+        entry = kernel
+            .read_addr_arch(arch, entry)
+            .data_part()
+            .unwrap_or_default();
+    }
+    threads
+}
+
+/// Reads the user mode stack range from the TEB of a thread in process memory.
+pub fn thread_info<T: MemoryView>(
+    mem: &mut T,
+    arch: ArchitectureObj,
+    offsets: &ThreadOffsets,
+    id: u32,
+    teb: Address,
+) -> ThreadInfo {
+    let pointer_size = arch.size_addr() as umem;
+
+    // `NT_TIB.StackBase` and `NT_TIB.StackLimit` are the first members after the exception list
+    let stack_base = mem
+        .read_addr_arch(arch, teb + pointer_size)
+        .data_part()
+        .ok();
+    let stack_limit = match offsets.deallocation_stack {
+        Some(offset) => mem.read_addr_arch(arch, teb + offset).data_part().ok(),
+        None => mem
+            .read_addr_arch(arch, teb + 2 * pointer_size)
+            .data_part()
+            .ok(),
+    };
+
+    let stack = match (stack_limit, stack_base) {
+        (Some(limit), Some(base)) if !limit.is_null() && limit < base => Some((limit, base)),
+        _ => None,
+    };
+
+    ThreadInfo {
+        id,
+        teb,
+        teb_size: offsets.teb_size,
+        stack,
+    }
+}